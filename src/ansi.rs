@@ -0,0 +1,42 @@
+/// Strip ANSI escape sequences (colors, cursor movement, etc) from a string.
+///
+/// NOTE: This is used when writing logs somewhere that can't render
+/// them, like a pipe or a file, where they would just be noise.
+pub fn strip(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // Control Sequence Introducer, ends with a byte in the range `@` to `~`
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating System Command, ends with a BEL or a String Terminator (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Any other escape sequence is just the escape and a single character
+            _ => {}
+        }
+    }
+
+    stripped
+}
@@ -0,0 +1,169 @@
+use crate::{cargo, error::Error};
+use std::{collections::BTreeSet, process::Command};
+
+/// What's built and ran as the app, when the package has several binaries (or examples).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunTarget {
+    /// A binary of the package (ex: `src/bin/server.rs`).
+    Bin(String),
+
+    /// An example of the package (ex: `examples/demo.rs`).
+    Example(String),
+}
+impl RunTarget {
+    /// Get the arguments to cargo selecting the target (ex: `--bin server`).
+    fn args(&self) -> [String; 2] {
+        match self {
+            RunTarget::Bin(name) => [String::from("--bin"), name.clone()],
+            RunTarget::Example(name) => [String::from("--example"), name.clone()],
+        }
+    }
+
+    /// Get the name of the target (ex: `server`).
+    pub fn name(&self) -> &str {
+        match self {
+            RunTarget::Bin(name) | RunTarget::Example(name) => name,
+        }
+    }
+}
+
+/// The options builds are ran with (profile, features and target),
+/// picked at runtime from the interface.
+///
+/// NOTE: The defaults are cargo's own, a debug build of the
+/// default features and binary, for the host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildOptions {
+    /// Build with the release profile (`--release`).
+    pub release: bool,
+
+    /// The features enabled on top of the default ones (`--features`).
+    pub features: BTreeSet<String>,
+
+    /// The binary (or example) built and ran, instead of cargo's default.
+    pub run_target: Option<RunTarget>,
+
+    /// The target triple built for (ex: `x86_64-unknown-linux-musl`), instead of the host.
+    pub target_triple: Option<String>,
+}
+impl BuildOptions {
+    /// Get the arguments to cargo selecting these options.
+    ///
+    /// NOTE: The binary (or example) to run is only selected when
+    /// building the app, checks and tests cover the whole package.
+    pub fn args(&self, with_run_target: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.release {
+            args.push(String::from("--release"));
+        }
+        if !self.features.is_empty() {
+            let features: Vec<&str> = self.features.iter().map(String::as_str).collect();
+            args.extend([String::from("--features"), features.join(",")]);
+        }
+        if with_run_target && let Some(run_target) = &self.run_target {
+            args.extend(run_target.args());
+        }
+        if let Some(target_triple) = &self.target_triple {
+            args.extend([String::from("--target"), target_triple.clone()]);
+        }
+
+        args
+    }
+
+    /// Are these cargo's default options ?
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Describe the options in a few words (ex: `release, +serde, bin server`).
+    pub fn summary(&self) -> String {
+        let mut words = vec![match self.release {
+            true => String::from("release"),
+            false => String::from("debug"),
+        }];
+        words.extend(self.features.iter().map(|feature| format!("+{feature}")));
+        match &self.run_target {
+            Some(RunTarget::Bin(name)) => words.push(format!("bin {name}")),
+            Some(RunTarget::Example(name)) => words.push(format!("example {name}")),
+            None => {}
+        }
+        if let Some(target_triple) = &self.target_triple {
+            words.push(target_triple.clone());
+        }
+
+        words.join(", ")
+    }
+}
+
+/// What can be picked in the `BuildOptions`, read from the
+/// package in the current directory and the installed toolchain.
+#[derive(Debug, Clone, Default)]
+pub struct BuildChoices {
+    /// The features of the package, besides `default`.
+    pub features: Vec<String>,
+
+    /// The binaries and examples of the package, when there's more than one of them.
+    pub run_targets: Vec<RunTarget>,
+
+    /// The target triples installed with rustup (ex: `wasm32-unknown-unknown`).
+    pub target_triples: Vec<String>,
+}
+impl BuildChoices {
+    /// Read the features, binaries and examples of the package in the current
+    /// directory with `cargo metadata`, and the installed targets with rustup.
+    ///
+    /// NOTE: Without rustup, only the host can be built for.
+    pub fn load() -> Result<Self, Error> {
+        let manifest_path = std::env::current_dir()?.join("Cargo.toml");
+        let metadata = cargo::Metadata::load()?;
+        let package = match &metadata.packages[..] {
+            [package] => Some(package),
+            packages => packages
+                .iter()
+                .find(|package| package.manifest_path == manifest_path),
+        };
+
+        let mut choices = Self::default();
+        if let Some(package) = package {
+            choices.features = package
+                .features
+                .keys()
+                .filter(|feature| *feature != "default")
+                .cloned()
+                .collect();
+
+            for target in &package.targets {
+                if target.kind.iter().any(|kind| kind == "bin") {
+                    choices
+                        .run_targets
+                        .push(RunTarget::Bin(target.name.clone()));
+                } else if target.kind.iter().any(|kind| kind == "example") {
+                    choices
+                        .run_targets
+                        .push(RunTarget::Example(target.name.clone()));
+                }
+            }
+            if choices.run_targets.len() < 2 {
+                choices.run_targets.clear();
+            }
+        }
+
+        choices.target_triples = Command::new("rustup")
+            .args(["target", "list", "--installed"])
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|line| line.trim().to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(choices)
+    }
+}
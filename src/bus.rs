@@ -0,0 +1,89 @@
+use crate::event::Event;
+use std::sync::{
+    Arc, Mutex,
+    mpsc::{self, Receiver, Sender},
+};
+
+/// Publishes events produced by `Watcher` to any number of subscribers,
+/// each receiving their own copy of every event over a channel.
+///
+/// NOTE: Cloning the bus is cheap and shares the same subscribers, so
+/// it can be handed to the threads reading output of build processes.
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    /// The sending half of each subscribers channel.
+    subscribers: Arc<Mutex<Vec<Sender<Event>>>>,
+}
+impl EventBus {
+    /// Create a new `EventBus` instance
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribe to the events published from now on.
+    pub fn subscribe(&self) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(sender);
+        }
+
+        receiver
+    }
+
+    /// Publish an event to every subscriber.
+    ///
+    /// NOTE: Subscribers which dropped their receiver are removed.
+    pub fn publish(&self, event: Event) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::BuildKind;
+
+    #[test]
+    fn every_subscriber_receives_every_event() {
+        let bus = EventBus::new();
+        let first = bus.subscribe();
+        let second = bus.subscribe();
+
+        bus.publish(Event::ChangeDetected {
+            paths: vec![String::from("src/main.rs")],
+        });
+        bus.clone().publish(Event::BuildStarted {
+            kind: BuildKind::Rust,
+        });
+
+        for receiver in [first, second] {
+            let events: Vec<Event> = receiver.try_iter().collect();
+            assert_eq!(events.len(), 2);
+            assert!(
+                matches!(&events[0], Event::ChangeDetected { paths } if paths == &["src/main.rs"])
+            );
+            assert!(matches!(
+                events[1],
+                Event::BuildStarted {
+                    kind: BuildKind::Rust
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn dropped_subscribers_are_removed() {
+        let bus = EventBus::new();
+        let kept = bus.subscribe();
+        drop(bus.subscribe());
+
+        bus.publish(Event::BuildStarted {
+            kind: BuildKind::Rust,
+        });
+
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+        assert_eq!(kept.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{error::Error, event::Diagnostic};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::PathBuf, process::Command};
+
+/// A message emitted by cargo when ran with `--message-format=json*`.
+///
+/// NOTE: Only the messages (and fields) we care about are
+/// parsed, everything else is ignored.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message {
+    #[serde(rename = "compiler-message")]
+    CompilerDiagnostic {
+        message: CompilerDiagnostic,
+    },
+    CompilerArtifact(Artifact),
+    BuildFinished {
+        success: bool,
+    },
+    #[serde(other)]
+    Other,
+}
+impl Message {
+    /// Try to parse a line of cargo output as a JSON message.
+    ///
+    /// NOTE: Returns `None` for anything not produced by cargo, like
+    /// the output of build scripts.
+    pub fn parse(line: &str) -> Option<Self> {
+        if !line.starts_with("{\"reason\":") {
+            return None;
+        }
+
+        serde_json::from_str(line).ok()
+    }
+}
+
+/// Something built by cargo (ex: a library, a binary or a build script).
+#[derive(Debug, Deserialize)]
+pub struct Artifact {
+    /// The `Cargo.toml` of the package it belongs to.
+    pub manifest_path: PathBuf,
+
+    /// What was built.
+    pub target: ArtifactTarget,
+
+    /// The path of the executable, if a binary was built.
+    pub executable: Option<PathBuf>,
+}
+impl Artifact {
+    /// Is this the executable of a binary target (ex: `src/main.rs`) ?
+    ///
+    /// NOTE: Tests, examples and build scripts have executables too.
+    pub fn is_bin(&self) -> bool {
+        self.executable.is_some() && self.target.kind.iter().any(|kind| kind == "bin")
+    }
+
+    /// Is this the executable of an example (ex: `examples/demo.rs`) ?
+    pub fn is_example(&self) -> bool {
+        self.executable.is_some() && self.target.kind.iter().any(|kind| kind == "example")
+    }
+}
+
+/// The target of a package an artifact was built for.
+#[derive(Debug, Deserialize)]
+pub struct ArtifactTarget {
+    /// The kinds of the target (ex: `bin`, `lib`, `custom-build`).
+    pub kind: Vec<String>,
+}
+
+/// A diagnostic produced by the compiler, as reported by cargo.
+#[derive(Debug, Deserialize)]
+pub struct CompilerDiagnostic {
+    level: String,
+    message: String,
+    code: Option<DiagnosticCode>,
+    spans: Vec<DiagnosticSpan>,
+    rendered: Option<String>,
+}
+impl From<CompilerDiagnostic> for Diagnostic {
+    fn from(msg: CompilerDiagnostic) -> Self {
+        let primary_span = msg.spans.into_iter().find(|span| span.is_primary);
+
+        Diagnostic {
+            level: msg.level,
+            message: msg.message,
+            code: msg.code.map(|code| code.code),
+            file: primary_span.as_ref().map(|span| span.file_name.clone()),
+            line: primary_span.as_ref().map(|span| span.line_start),
+            column: primary_span.as_ref().map(|span| span.column_start),
+            rendered: msg.rendered,
+        }
+    }
+}
+
+/// The code of a compiler diagnostic (ex: E0425).
+#[derive(Debug, Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+/// The location in the source code a compiler diagnostic points to.
+#[derive(Debug, Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+/// The output of `cargo metadata --no-deps`, describing the packages of a workspace.
+#[derive(Debug, Deserialize)]
+pub struct Metadata {
+    /// The packages of the workspace.
+    pub packages: Vec<Package>,
+}
+impl Metadata {
+    /// Describe the packages of the workspace the current directory is in with `cargo metadata`.
+    pub fn load() -> Result<Self, Error> {
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version=1", "--no-deps"])
+            .stdin(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::InvalidCommand(format!(
+                "`cargo metadata` failed\n{}",
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            )));
+        }
+
+        serde_json::from_slice(&output.stdout).map_err(|e| {
+            Error::InvalidCommand(format!(
+                "failed to parse the output of `cargo metadata`: {e}"
+            ))
+        })
+    }
+}
+
+/// A package of a workspace.
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    /// The name of the package (ex: `frontend-utils`).
+    pub name: String,
+
+    /// The `Cargo.toml` of the package.
+    pub manifest_path: PathBuf,
+
+    /// The packages this package depends on.
+    pub dependencies: Vec<Dependency>,
+
+    /// The features of the package, and what each one enables.
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+
+    /// What the package builds (ex: its library, binaries and examples).
+    #[serde(default)]
+    pub targets: Vec<PackageTarget>,
+}
+
+/// Something a package builds (ex: a binary or an example).
+#[derive(Debug, Deserialize)]
+pub struct PackageTarget {
+    /// The name of the target (ex: `server`).
+    pub name: String,
+
+    /// The kinds of the target (ex: `bin`, `example`, `lib`).
+    pub kind: Vec<String>,
+}
+
+/// A dependency of a package.
+#[derive(Debug, Deserialize)]
+pub struct Dependency {
+    /// The name of the package depended on.
+    pub name: String,
+
+    /// The kind of dependency, `None` for normal dependencies (ex: `dev`, `build`).
+    pub kind: Option<String>,
+
+    /// Where the package depended on is, for path dependencies (ex: workspace members).
+    pub path: Option<PathBuf>,
+}
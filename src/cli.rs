@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+use unlimited_ammo::{Error, LogFormat, Mode, VERSION};
+
+/// The default size (in megabytes) a log file can grow before being rotated.
+const DEFAULT_LOG_MAX_SIZE_MB: u64 = 10;
+
+/// The command line arguments for Unlimited Ammo.
+#[derive(Debug)]
+pub struct Args {
+    /// Write log messages straight to stdout/stderr
+    /// instead of running the terminal user interface.
+    pub no_tui: bool,
+
+    /// Write events to stdout as newline delimited JSON,
+    /// for other tools to consume (ex: editor plugins).
+    pub json: bool,
+
+    /// Run the app inside a pseudo-terminal, so it
+    /// sees a real terminal instead of pipes.
+    pub pty: bool,
+
+    /// Mirror every log entry into this file.
+    pub log_file: Option<PathBuf>,
+
+    /// The format log entries are written to the log file in.
+    pub log_format: LogFormat,
+
+    /// How big (in bytes) the log file can grow before being rotated.
+    pub log_max_size: u64,
+
+    /// Use this config file instead of looking for one.
+    pub config: Option<PathBuf>,
+
+    /// What to do with the codebase when it changes.
+    pub mode: Mode,
+}
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            no_tui: false,
+            json: false,
+            pty: false,
+            log_file: None,
+            log_format: LogFormat::default(),
+            log_max_size: DEFAULT_LOG_MAX_SIZE_MB * 1024 * 1024,
+            config: None,
+            mode: Mode::default(),
+        }
+    }
+}
+impl Args {
+    /// Parse the command line arguments passed to the process.
+    ///
+    /// NOTE: `--help` and `--version` print their message and exit.
+    pub fn parse() -> Result<Self, Error> {
+        let mut args = Self::default();
+        let mut raw_args = std::env::args().skip(1);
+
+        while let Some(arg) = raw_args.next() {
+            match arg.as_str() {
+                "--no-tui" => args.no_tui = true,
+                "--json" => args.json = true,
+                "--pty" => args.pty = true,
+                "-c" | "--config" => {
+                    args.config = Some(PathBuf::from(Self::value(&arg, raw_args.next())?));
+                }
+                "-m" | "--mode" => {
+                    let value = Self::value(&arg, raw_args.next())?;
+                    args.mode = Mode::from_name(&value).ok_or(Error::InvalidArgument(value))?;
+                }
+                "--log-file" => {
+                    args.log_file = Some(PathBuf::from(Self::value(&arg, raw_args.next())?));
+                }
+                "--log-format" => {
+                    args.log_format = match Self::value(&arg, raw_args.next())?.as_str() {
+                        "text" => LogFormat::Text,
+                        "jsonl" => LogFormat::Jsonl,
+                        value => return Err(Error::InvalidArgument(value.to_string())),
+                    };
+                }
+                "--log-max-size" => {
+                    let value = Self::value(&arg, raw_args.next())?;
+                    // NOTE: A size of 0 would rotate the log file on every entry.
+                    args.log_max_size = value
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|megabytes| *megabytes > 0)
+                        .and_then(|megabytes| megabytes.checked_mul(1024 * 1024))
+                        .ok_or(Error::InvalidArgument(value))?;
+                }
+                "-h" | "--help" => {
+                    println!("{}", Self::help());
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("unlimited-ammo {VERSION}");
+                    std::process::exit(0);
+                }
+                _ => return Err(Error::InvalidArgument(arg)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Get the value following an argument which requires one.
+    fn value(arg: &str, value: Option<String>) -> Result<String, Error> {
+        value.ok_or_else(|| Error::MissingArgumentValue(arg.to_string()))
+    }
+
+    /// Get the help message describing the command line arguments.
+    fn help() -> String {
+        format!(
+            "Unlimited Ammo {VERSION}
+Hot reloader for Rust codebases, never waste time rebuilding again.
+
+Usage: unlimited-ammo [OPTIONS]
+
+Options:
+  -c, --config <PATH>       Use this config file, instead of `unlimited-ammo.toml` in the
+                            project or `~/.config/unlimited-ammo/config.toml`
+  -m, --mode <MODE>         What to do on changes: run (build and run the app), test (run the
+                            tests, with cargo nextest when installed), check or clippy (only
+                            report diagnostics), switchable from the interface [default: run]
+      --no-tui              Write logs straight to stdout/stderr instead of running the
+                            terminal user interface (default when stdout isn't a terminal)
+      --json                Write events to stdout as newline delimited JSON, for
+                            other tools to consume (ex: editor plugins)
+      --pty                 Run the app inside a pseudo-terminal sized to its pane, so it
+                            sees a real terminal (ex: colors, progress bars)
+      --log-file <PATH>     Mirror every log entry into a file, rotated as it grows
+      --log-format <FORMAT> The format of the log file: text or jsonl [default: text]
+      --log-max-size <MB>   How big the log file grows before being rotated [default: {DEFAULT_LOG_MAX_SIZE_MB}]
+  -h, --help                Print help
+  -V, --version             Print version"
+        )
+    }
+}
@@ -0,0 +1,319 @@
+use crate::error::Error;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The name of the config file looked for in the project being watched.
+pub const PROJECT_CONFIG_FILE: &str = "unlimited-ammo.toml";
+
+/// The configuration of Unlimited Ammo, loaded from a TOML file.
+///
+/// NOTE: Every section is optional, anything left out uses its default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The theme of the terminal user interface.
+    pub theme: ThemeConfig,
+
+    /// The key bindings of the terminal user interface.
+    pub keys: KeysConfig,
+
+    /// The mouse support of the terminal user interface.
+    pub mouse: MouseConfig,
+
+    /// How the app is ran (ex: restarting it when it crashes).
+    pub run: RunConfig,
+
+    /// Shell commands to run around building and running the app.
+    pub hooks: HooksConfig,
+
+    /// What to do about changes to specific paths.
+    pub triggers: Vec<TriggerConfig>,
+
+    /// Long-running processes supervised along with the app (ex: a worker).
+    pub processes: Vec<ProcessConfig>,
+
+    /// How the codebase is linted in clippy mode.
+    pub clippy: ClippyConfig,
+}
+impl Config {
+    /// Load the config file, looking in order for:
+    ///
+    /// 1. `unlimited-ammo.toml` in the project being watched.
+    /// 2. `$XDG_CONFIG_HOME/unlimited-ammo/config.toml`
+    ///    (or `~/.config/unlimited-ammo/config.toml`).
+    ///
+    /// NOTE: The default config is used if neither exist.
+    pub fn load() -> Result<Self, Error> {
+        let project_config = PathBuf::from(PROJECT_CONFIG_FILE);
+        if project_config.exists() {
+            return Self::load_from(&project_config);
+        }
+
+        match Self::user_config_path() {
+            Some(user_config) if user_config.exists() => Self::load_from(&user_config),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a specific config file.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|e| Error::InvalidConfig(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Get the path of the users config file, following the XDG base
+    /// directory spec (ex: `~/.config/unlimited-ammo/config.toml`).
+    fn user_config_path() -> Option<PathBuf> {
+        let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+
+        Some(config_dir.join("unlimited-ammo").join("config.toml"))
+    }
+}
+
+/// The `[theme]` section of the config.
+///
+/// ```toml
+/// [theme]
+/// preset = "light"
+///
+/// [theme.styles]
+/// app_title = { fg = "magenta", bold = true }
+/// selection = { bg = "#263a5e" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The built in theme to start from: `dark`, `light` or `high-contrast`.
+    pub preset: Option<String>,
+
+    /// Overrides of individual styles in the preset, by name.
+    pub styles: BTreeMap<String, StyleConfig>,
+}
+
+/// The `[keys]` section of the config.
+///
+/// ```toml
+/// [keys]
+/// preset = "emacs"
+///
+/// [keys.bindings]
+/// quit = ["ctrl-c", "q"]
+/// toggle_follow = ["F"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    /// The built in keymap to start from: `vim`, `emacs` or `arrows`.
+    pub preset: Option<String>,
+
+    /// Overrides of the keys bound to individual actions in the preset, by name.
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+/// The `[mouse]` section of the config.
+///
+/// ```toml
+/// [mouse]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MouseConfig {
+    /// Should the interface capture the mouse (scrolling, clicking) ?
+    ///
+    /// NOTE: While captured, the terminal's own text selection
+    /// usually needs a modifier held (ex: shift) to work.
+    pub enabled: bool,
+}
+impl Default for MouseConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// The `[run]` section of the config.
+///
+/// ```toml
+/// [run]
+/// restart = "on-failure"
+/// max_restarts = 5
+/// restart_delay_ms = 500
+///
+/// [run.ready]
+/// http = "http://localhost:8080/health"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    /// When to restart the app after it exits on it's own:
+    /// `never`, `on-failure` or `always`.
+    pub restart: Option<String>,
+
+    /// How many times in a row the app is restarted before giving up.
+    pub max_restarts: u32,
+
+    /// How long (in milliseconds) to wait before restarting the
+    /// app, doubled every time it's restarted in a row.
+    pub restart_delay_ms: u64,
+
+    /// How to tell when the app is ready (ex: accepting connections).
+    pub ready: ReadyConfig,
+}
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            restart: None,
+            max_restarts: 5,
+            restart_delay_ms: 500,
+            ready: ReadyConfig::default(),
+        }
+    }
+}
+
+/// The `[run.ready]` section of the config, a readiness probe.
+///
+/// NOTE: Only one of `tcp`, `http` or `log` can be set.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadyConfig {
+    /// Ready once this address (ex: `localhost:8080`, or just a port) accepts connections.
+    pub tcp: Option<String>,
+
+    /// Ready once a GET request to this URL responds with a 2xx status.
+    pub http: Option<String>,
+
+    /// Ready once the app logs a line matching this regular expression.
+    pub log: Option<String>,
+
+    /// How often (in milliseconds) to check if the app is ready.
+    pub interval_ms: u64,
+}
+impl Default for ReadyConfig {
+    fn default() -> Self {
+        Self {
+            tcp: None,
+            http: None,
+            log: None,
+            interval_ms: 250,
+        }
+    }
+}
+
+/// The `[hooks]` section of the config, shell commands
+/// ran at points in the cycle of building and running the app.
+///
+/// ```toml
+/// [hooks]
+/// pre_build = "cargo sqlx prepare"
+/// post_build = "sqlx migrate run"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Ran when changes are detected, before anything else.
+    pub on_change: Option<String>,
+
+    /// Ran before building, the build is skipped if it fails.
+    pub pre_build: Option<String>,
+
+    /// Ran after building, before the app is restarted.
+    pub post_build: Option<String>,
+
+    /// Ran once the app is ready.
+    pub on_ready: Option<String>,
+
+    /// Ran when the app exits.
+    pub on_exit: Option<String>,
+}
+
+/// A `[[triggers]]` rule of the config, what to do about changes to
+/// paths matching a glob, instead of rebuilding and restarting the app.
+///
+/// ```toml
+/// [[triggers]]
+/// glob = "migrations/**"
+/// run = "sqlx migrate run"
+/// actions = ["restart"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TriggerConfig {
+    /// The paths the rule applies to, a pattern without a `/`
+    /// (ex: `*.proto`) matches file names in any directory.
+    pub glob: String,
+
+    /// A shell command to run first (ex: code generation).
+    #[serde(default)]
+    pub run: Option<String>,
+
+    /// What else to do: `notify`, `build_web`, `rebuild` or `restart`.
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+/// A `[[processes]]` entry of the config, a long-running process
+/// supervised along with the app, Procfile style.
+///
+/// ```toml
+/// [[processes]]
+/// name = "worker"
+/// command = "cargo run --bin worker"
+/// restart_on = ["crates/worker/**"]
+/// color = "yellow"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProcessConfig {
+    /// The name of the process (ex: `worker`), `app` is the app itself.
+    pub name: String,
+
+    /// The shell command running the process.
+    pub command: String,
+
+    /// Restart the process on changes to paths matching these globs.
+    #[serde(default)]
+    pub restart_on: Vec<String>,
+
+    /// The color of the prefix of its logs (ex: `yellow`, `bright-blue`).
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// The prefix of its logs, defaults to its name.
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+/// The `[clippy]` section of the config, how the codebase is linted in clippy mode.
+///
+/// ```toml
+/// [clippy]
+/// args = ["-W", "clippy::pedantic", "-A", "clippy::module_name_repetitions"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClippyConfig {
+    /// The arguments passed to clippy after `--`, usually lint levels (ex: `-D warnings`).
+    pub args: Vec<String>,
+}
+
+/// A style override within the `[theme.styles]` section of the config.
+///
+/// NOTE: Colors are names (ex: `green`, `dark-gray`), an indexed
+/// color (ex: `236`), or a hex RGB color (ex: `#080808`).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub reversed: Option<bool>,
+}
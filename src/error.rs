@@ -2,7 +2,10 @@
 pub enum Error {
     StdIo(std::io::Error),
     WatchIntervalNotSet,
-    DisplayNotSet,
+    InvalidArgument(String),
+    MissingArgumentValue(String),
+    InvalidConfig(String),
+    InvalidCommand(String),
     BuildFailed(std::io::Error),
     FailedParsingOsString(std::ffi::OsString),
 }
@@ -18,7 +21,14 @@ impl Error {
             Self::FailedParsingOsString(e) => {
                 format!("Error: Failed parsing OS Native string: {:?}", e)
             }
-            Self::DisplayNotSet => String::from("Error: Log display not configured correctly."),
+            Self::InvalidArgument(arg) => {
+                format!("Error: Invalid argument `{arg}`, see `unlimited-ammo --help`.")
+            }
+            Self::MissingArgumentValue(arg) => {
+                format!("Error: Missing a value for argument `{arg}`.")
+            }
+            Self::InvalidConfig(e) => format!("Error: Invalid config\n{}", e),
+            Self::InvalidCommand(e) => format!("Error: {e}"),
         }
     }
 }
@@ -28,6 +38,16 @@ impl std::fmt::Display for Error {
         write!(f, "{}", self.message())
     }
 }
+/// Implement the error trait for `Error`, so it works with `?`
+/// into `Box<dyn std::error::Error>` (and the likes of anyhow).
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::StdIo(e) | Self::BuildFailed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 /// Implement error conversion (`std::io::Error` -> `Error`)
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Error {
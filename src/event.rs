@@ -0,0 +1,196 @@
+use crate::mode::Mode;
+use serde::Serialize;
+
+/// Something that happened while `Watcher` was watching the project.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The mode of the `Watcher` was set (on start), or switched to another one.
+    ModeChanged { mode: Mode },
+
+    /// Changes were detected in the watched files.
+    ChangeDetected { paths: Vec<String> },
+
+    /// A build was started.
+    BuildStarted { kind: BuildKind },
+
+    /// The compiler reported a diagnostic (error, warning, etc).
+    Diagnostic(Diagnostic),
+
+    /// A build finished, successfully or not.
+    BuildFinished {
+        kind: BuildKind,
+        success: bool,
+        duration_ms: u128,
+    },
+
+    /// A supervised process (the app, or one declared in the config) was
+    /// started, `name` is `app` for the app itself.
+    ProcessStarted { name: String, pid: u32 },
+
+    /// A process passed its readiness probe (or was started, without
+    /// one), `duration_ms` after the change (or restart) leading to it.
+    Ready {
+        name: String,
+        pid: u32,
+        duration_ms: u128,
+    },
+
+    /// A process exited, `code` is `None` when it was killed.
+    ProcessExited {
+        name: String,
+        pid: u32,
+        code: Option<i32>,
+    },
+
+    /// A process exited on it's own, and will be restarted after a
+    /// delay (see `RestartPolicy`), the `attempt` in a row out of `max_attempts`.
+    RestartScheduled {
+        name: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u128,
+    },
+
+    /// A process kept exiting right after being restarted, so it won't
+    /// be restarted again until the next change (or build) restarting it.
+    CrashLoop { name: String, restarts: u32 },
+
+    /// A test finished running (in test mode), `name` is its full
+    /// path (ex: `config::tests::parses_hooks`).
+    TestResult { name: String, outcome: TestOutcome },
+
+    /// A test run finished, with the counts of each outcome
+    /// and the captured output of every failed test.
+    TestsFinished {
+        passed: usize,
+        failed: usize,
+        ignored: usize,
+        failures: Vec<TestFailure>,
+    },
+
+    /// A log message was produced.
+    Log { source: LogSource, line: String },
+}
+
+/// The kind of build being ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildKind {
+    /// The rust codebase.
+    Rust,
+
+    /// The web frontend (`src/web`).
+    Web,
+
+    /// The tests of the rust codebase (in test mode), built and ran.
+    Tests,
+
+    /// The rust codebase checked with `cargo check` (in check mode).
+    Check,
+
+    /// The rust codebase linted with `cargo clippy` (in clippy mode).
+    Clippy,
+}
+impl BuildKind {
+    /// Get the name of the build, as shown to users (ex: `test run`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildKind::Rust => "rust build",
+            BuildKind::Web => "web build",
+            BuildKind::Tests => "test run",
+            BuildKind::Check => "cargo check",
+            BuildKind::Clippy => "cargo clippy",
+        }
+    }
+}
+
+/// How a test ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// A test which failed, and what it printed while running.
+#[derive(Debug, Clone, Serialize)]
+pub struct TestFailure {
+    /// The full path of the test (ex: `config::tests::parses_hooks`).
+    pub name: String,
+
+    /// The output captured by the test runner (ex: the panic message).
+    pub output: Vec<String>,
+}
+
+/// Where a log message originated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSource {
+    /// Unlimited Ammo itself.
+    Watcher,
+
+    /// The compiler and cargo, while building the rust codebase.
+    Build,
+
+    /// The web build tool, while building the web frontend.
+    Web,
+
+    /// The stdout of the running app (and the other supervised processes).
+    Stdout,
+
+    /// The stderr of the running app (and the other supervised processes).
+    Stderr,
+}
+impl LogSource {
+    /// Every log source.
+    pub const ALL: [LogSource; 5] = [
+        LogSource::Watcher,
+        LogSource::Build,
+        LogSource::Web,
+        LogSource::Stdout,
+        LogSource::Stderr,
+    ];
+
+    /// Get the name of the log source (ex: `stderr`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogSource::Watcher => "watcher",
+            LogSource::Build => "build",
+            LogSource::Web => "web",
+            LogSource::Stdout => "stdout",
+            LogSource::Stderr => "stderr",
+        }
+    }
+
+    /// Get a log source by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.name() == name)
+    }
+}
+
+/// A diagnostic message reported by the compiler.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// The severity of the diagnostic (ex: error, warning).
+    pub level: String,
+
+    /// The main diagnostic message.
+    pub message: String,
+
+    /// The diagnostic code (ex: E0425), if it has one.
+    pub code: Option<String>,
+
+    /// The file the diagnostic points to, if any.
+    pub file: Option<String>,
+
+    /// The line the diagnostic points to, if any.
+    pub line: Option<usize>,
+
+    /// The column the diagnostic points to, if any.
+    pub column: Option<usize>,
+
+    /// The diagnostic rendered the way the compiler prints it.
+    pub rendered: Option<String>,
+}
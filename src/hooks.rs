@@ -0,0 +1,72 @@
+use crate::config::HooksConfig;
+
+/// A point in the cycle of building and running the app
+/// where the user can run their own shell command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    /// Changes were detected, before anything else
+    /// (with the changed paths in `AMMO_CHANGED_PATHS`).
+    OnChange,
+
+    /// Before building, the build is skipped if this fails.
+    PreBuild,
+
+    /// After building, before the app is restarted
+    /// (with `AMMO_BUILD_SUCCESS` set to `true` or `false`).
+    PostBuild,
+
+    /// A process is ready (with it's name in `AMMO_PROCESS`, `app` for
+    /// the app, it's PID in `AMMO_PID`, and how long it took to be
+    /// ready in `AMMO_READY_MS`).
+    OnReady,
+
+    /// A process exited (with it's name in `AMMO_PROCESS`, it's PID in
+    /// `AMMO_PID`, and it's exit code in `AMMO_EXIT_CODE`, empty when it
+    /// was killed).
+    OnExit,
+}
+impl Hook {
+    /// Get the name of the hook (ex: `pre_build`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::OnChange => "on_change",
+            Hook::PreBuild => "pre_build",
+            Hook::PostBuild => "post_build",
+            Hook::OnReady => "on_ready",
+            Hook::OnExit => "on_exit",
+        }
+    }
+}
+
+/// The shell commands to run at each hook.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    on_change: Option<String>,
+    pre_build: Option<String>,
+    post_build: Option<String>,
+    on_ready: Option<String>,
+    on_exit: Option<String>,
+}
+impl Hooks {
+    /// Create the hooks described in the `[hooks]` section of the config.
+    pub fn from_config(config: &HooksConfig) -> Self {
+        Self {
+            on_change: config.on_change.clone(),
+            pre_build: config.pre_build.clone(),
+            post_build: config.post_build.clone(),
+            on_ready: config.on_ready.clone(),
+            on_exit: config.on_exit.clone(),
+        }
+    }
+
+    /// Get the shell command to run at a hook, if there is one.
+    pub fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::OnChange => self.on_change.as_deref(),
+            Hook::PreBuild => self.pre_build.as_deref(),
+            Hook::PostBuild => self.post_build.as_deref(),
+            Hook::OnReady => self.on_ready.as_deref(),
+            Hook::OnExit => self.on_exit.as_deref(),
+        }
+    }
+}
@@ -1,55 +1,193 @@
 use crate::{
-    error::Error,
-    interface::{Display, THEME},
     VERSION,
+    error::Error,
+    event::LogSource,
+    interface::{
+        Action, BuildMenu, Display, Keymap, Palette, PaletteCommand, PaneLayout, Split, Tab, Tabs,
+        editor::{Editor, Location},
+        export,
+        input::{InputEvent, InputPrompt},
+        palette::PaletteInput,
+        theme,
+    },
+    mode::Mode,
+    process::{APP, Processes},
+    watcher::{Watcher, WatcherCommand},
 };
 use ratatui::{
+    DefaultTerminal, Frame,
     buffer::Buffer,
-    crossterm::event::{Event, KeyCode, KeyEventKind},
-    layout::{Constraint, Layout, Rect},
-    style::Color,
+    crossterm::event::{Event, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind},
+    layout::{Constraint, Layout, Position, Rect},
     text::{Line, Span},
-    widgets::{Block, Paragraph, Widget},
-    DefaultTerminal, Frame,
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
 use std::{
+    cell::RefCell,
+    io::stdout,
     sync::{
-        atomic::{AtomicBool, Ordering},
         Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
     },
     time::Duration,
 };
 
+/// What's shown over the interface, taking the users key presses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    None,
+
+    /// The help screen, listing every action and command.
+    Help {
+        scroll: usize,
+    },
+
+    /// The command palette, entering a command.
+    Palette,
+
+    /// The input mode, forwarding what's typed to the running app.
+    Input,
+
+    /// The build menu, picking the options of the next builds.
+    BuildMenu,
+}
+
 #[derive(Debug)]
 /// The Terminal User Interface (TUI) Application.
 pub struct App {
-    /// The display component of the `App`.
-    display: Arc<Mutex<Display>>,
+    /// The tabs of the `App`, each with their own display component.
+    tabs: Arc<Mutex<Tabs>>,
 
     /// Is the application running ?
     running: Arc<AtomicBool>,
 
-    /// The currently running build process.
-    current_build_process: Arc<Mutex<Option<std::process::Child>>>,
+    /// The running processes, the app and the other supervised processes.
+    processes: Arc<Mutex<Processes>>,
+
+    /// The keys bound to each action.
+    keymap: Keymap,
+
+    /// Where commands are sent to the running `Watcher`.
+    commands: Sender<WatcherCommand>,
+
+    /// What's shown over the interface, if anything.
+    overlay: Overlay,
+
+    /// The command palette, remembering previously entered commands.
+    palette: Palette,
+
+    /// The input mode prompt, forwarding what's typed to the running app.
+    input: InputPrompt,
+
+    /// The build menu, remembering the options picked.
+    build_menu: BuildMenu,
+
+    /// Is the mouse captured by the interface (scrolling, clicking) ?
+    mouse_enabled: bool,
+
+    /// Where the clickable parts of the interface (tabs, command bar
+    /// buttons) were drawn during the last render, and what they do.
+    buttons: RefCell<Vec<(Rect, Action)>>,
+
+    /// A location clicked on, to open in the users editor.
+    location_to_open: Option<Location>,
+
+    /// The size (columns, rows) last sent for the app's pseudo-terminal.
+    pty_size: Option<(u16, u16)>,
 }
 impl App {
     /// Create a new instance of `App`.
     pub fn new(
-        display: Arc<Mutex<Display>>,
-        build_process: Arc<Mutex<Option<std::process::Child>>>,
+        tabs: Arc<Mutex<Tabs>>,
+        processes: Arc<Mutex<Processes>>,
+        keymap: Keymap,
+        commands: Sender<WatcherCommand>,
+        mouse_enabled: bool,
     ) -> Self {
         Self {
-            display,
-            current_build_process: build_process,
+            tabs,
+            processes,
+            keymap,
+            commands,
+            overlay: Overlay::None,
+            palette: Palette::default(),
+            input: InputPrompt::default(),
+            build_menu: BuildMenu::default(),
+            mouse_enabled,
+            buttons: RefCell::new(Vec::new()),
+            location_to_open: None,
+            pty_size: None,
             running: Arc::new(AtomicBool::new(true)),
         }
     }
 
     /// Run the application, which draws the interface.
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<(), Error> {
+        Self::capture_mouse(self.mouse_enabled)?;
+
         while self.running.load(std::sync::atomic::Ordering::SeqCst) {
             terminal.draw(|frame| self.draw(frame))?;
+            self.update_pty_size();
+            self.build_menu.receive_choices();
             self.handle_events()?;
+
+            if let Some(location) = self.location_to_open.take() {
+                self.open_in_editor(&mut terminal, location)?;
+            }
+        }
+
+        Self::capture_mouse(false)
+    }
+
+    /// Start or stop capturing the mouse in the terminal.
+    fn capture_mouse(capture: bool) -> Result<(), Error> {
+        match capture {
+            true => crossterm::execute!(stdout(), crossterm::event::EnableMouseCapture)?,
+            false => crossterm::execute!(stdout(), crossterm::event::DisableMouseCapture)?,
+        }
+
+        Ok(())
+    }
+
+    /// Open a location in the users editor, stepping aside
+    /// while it's open if it runs in the terminal.
+    fn open_in_editor(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        location: Location,
+    ) -> Result<(), Error> {
+        let editor = Editor::from_env();
+        let mut command = editor.command(&location);
+        let name = format!("{}:{}", location.path.display(), location.line);
+
+        if !editor.runs_in_terminal() {
+            let msg = match command.spawn() {
+                Ok(mut child) => {
+                    // NOTE: Reap the editor whenever it's closed.
+                    std::thread::spawn(move || child.wait());
+                    format!("opened {name} in the editor")
+                }
+                Err(e) => format!("failed to open {name} in the editor: {e}"),
+            };
+            self.with_display(|display| display.add_message(&msg));
+            return Ok(());
+        }
+
+        Self::capture_mouse(false)?;
+        crossterm::execute!(stdout(), crossterm::terminal::LeaveAlternateScreen)?;
+        crossterm::terminal::disable_raw_mode()?;
+
+        let status = command.status();
+
+        crossterm::terminal::enable_raw_mode()?;
+        crossterm::execute!(stdout(), crossterm::terminal::EnterAlternateScreen)?;
+        Self::capture_mouse(self.mouse_enabled)?;
+        terminal.clear()?;
+
+        if let Err(e) = status {
+            let msg = format!("failed to open {name} in the editor: {e}");
+            self.with_display(|display| display.add_message(&msg));
         }
 
         Ok(())
@@ -60,6 +198,30 @@ impl App {
         frame.render_widget(self, frame.area());
     }
 
+    /// Size the pseudo-terminal the app runs in (if any) to the pane
+    /// showing the app's output, following it as the layout changes.
+    ///
+    /// NOTE: Falls back to the focused pane until the app's tab was shown.
+    fn update_pty_size(&mut self) {
+        let Ok(mut tabs) = self.tabs.lock() else {
+            return;
+        };
+        let area = match tabs.display_of(Tab::App).area {
+            area if !area.is_empty() => area,
+            _ => tabs.display().area,
+        };
+        drop(tabs);
+
+        let size = (area.width.saturating_sub(2), area.height);
+        if size.0 > 0 && size.1 > 0 && self.pty_size != Some(size) {
+            self.pty_size = Some(size);
+            self.send_command(WatcherCommand::Resize {
+                columns: size.0,
+                rows: size.1,
+            });
+        }
+    }
+
     /// Handle the queue of user events triggered in the `App`.
     fn handle_events(&mut self) -> Result<(), Error> {
         if crossterm::event::poll(Duration::from_millis(100))? {
@@ -80,78 +242,666 @@ impl App {
     /// Handle a specifc user event triggered in the `App`.
     fn handle_event(&mut self, event: crossterm::event::Event) -> Result<(), Error> {
         match event {
-            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
-                // Handle close app event
-                KeyCode::Char('q') | KeyCode::Esc => {
-                    self.running.store(false, Ordering::SeqCst);
-                    self.shutdown();
+            Event::Key(key) if key.kind == KeyEventKind::Press => match self.overlay {
+                Overlay::Help { scroll } => self.handle_help_key(key, scroll),
+                Overlay::Palette => self.handle_palette_key(key),
+                Overlay::Input => self.handle_input_key(key),
+                Overlay::BuildMenu => self.handle_build_menu_key(key),
+                Overlay::None => {
+                    // NOTE: A key can be bound to several actions (ex: `Esc`
+                    // cancels the selection, otherwise quits), the first one
+                    // applying to the current state of the `App` is performed.
+                    for action in self.keymap.actions(&key) {
+                        if self.perform(action) {
+                            break;
+                        }
+                    }
+                }
+            },
+            Event::Mouse(mouse) => self.handle_mouse(mouse),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Handle a mouse event, scrolling the pane under the wheel or
+    /// clicking on a tab, a button, a row, or a location (`path:line`).
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let position = Position::new(mouse.column, mouse.row);
+
+        if let Overlay::Help { scroll } = self.overlay {
+            let scroll = match mouse.kind {
+                MouseEventKind::ScrollUp => scroll.saturating_sub(3),
+                MouseEventKind::ScrollDown => scroll + 3,
+                _ => return,
+            };
+            let max_scroll = self.help_lines().len().saturating_sub(1);
+            self.overlay = Overlay::Help {
+                scroll: scroll.min(max_scroll),
+            };
+            return;
+        }
+        if self.overlay != Overlay::None {
+            return;
+        }
+
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            let button = self
+                .buttons
+                .borrow()
+                .iter()
+                .find(|(area, _)| area.contains(position))
+                .map(|(_, action)| *action);
+            if let Some(action) = button {
+                self.perform(action);
+                return;
+            }
+        }
+
+        let Ok(mut tabs) = self.tabs.lock() else {
+            return;
+        };
+        let Some(pane) = tabs.layout.visible_panes().into_iter().find(|pane| {
+            let tab = tabs.layout.panes[*pane];
+            tabs.display_of(tab).area.contains(position)
+        }) else {
+            return;
+        };
+        let tab = tabs.layout.panes[pane];
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => tabs.display_of(tab).wheel_down(),
+            MouseEventKind::ScrollUp => tabs.display_of(tab).wheel_up(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if tabs.layout.focused != pane {
+                    tabs.focus_next_pane();
+                    tabs.layout.save();
                 }
-                // Handle scroll up event
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if let Ok(mut display) = self.display.lock() {
-                        display.prev_row()
+
+                let display = tabs.display_of(tab);
+                let location = display
+                    .line_at(mouse.column, mouse.row)
+                    .and_then(|(line, column)| Location::find_at(&line, column));
+                match location {
+                    Some(location) => self.location_to_open = Some(location),
+                    None => {
+                        if let Some(row) = display.row_at(mouse.column, mouse.row) {
+                            display.select_row(row);
+                        }
                     }
                 }
-                // Handle scroll down event
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if let Ok(mut display) = self.display.lock() {
-                        display.next_row()
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle a key press while the help screen is shown,
+    /// scrolling through it or closing it.
+    fn handle_help_key(&mut self, key: KeyEvent, scroll: usize) {
+        let max_scroll = self.help_lines().len().saturating_sub(1);
+        for action in self.keymap.actions(&key) {
+            let scroll = match action {
+                Action::ScrollUp => scroll.saturating_sub(1),
+                Action::ScrollDown => scroll + 1,
+                Action::HalfPageUp | Action::PageUp => scroll.saturating_sub(10),
+                Action::HalfPageDown | Action::PageDown => scroll + 10,
+                Action::Top => 0,
+                Action::Bottom => max_scroll,
+                Action::ShowHelp | Action::Quit | Action::CancelSelection => {
+                    self.overlay = Overlay::None;
+                    return;
+                }
+                _ => continue,
+            };
+
+            self.overlay = Overlay::Help {
+                scroll: scroll.min(max_scroll),
+            };
+            return;
+        }
+    }
+
+    /// Handle a key press while entering a command in the command palette.
+    fn handle_palette_key(&mut self, key: KeyEvent) {
+        match self.palette.handle_key(key) {
+            PaletteInput::Editing => {}
+            PaletteInput::Cancelled => self.overlay = Overlay::None,
+            PaletteInput::Submitted(input) => {
+                self.overlay = Overlay::None;
+                self.run_command(&input);
+            }
+        }
+    }
+
+    /// Handle a key press while the build menu is shown, moving through it,
+    /// picking options, or closing it (applying the options picked).
+    fn handle_build_menu_key(&mut self, key: KeyEvent) {
+        for action in self.keymap.actions(&key) {
+            match action {
+                Action::PickBuildOption => self.build_menu.toggle(),
+                Action::ScrollUp => self.build_menu.move_cursor(-1),
+                Action::ScrollDown => self.build_menu.move_cursor(1),
+                Action::HalfPageUp | Action::PageUp => self.build_menu.move_cursor(-10),
+                Action::HalfPageDown | Action::PageDown => self.build_menu.move_cursor(10),
+                Action::Top => self.build_menu.move_cursor(isize::MIN),
+                Action::Bottom => self.build_menu.move_cursor(isize::MAX),
+                Action::OpenBuildMenu | Action::ApplyBuildOptions => {
+                    self.overlay = Overlay::None;
+                    if let Some(build_options) = self.build_menu.close() {
+                        self.send_command(WatcherCommand::SetBuildOptions(build_options));
                     }
                 }
-                _ => {}
+                Action::DiscardBuildOptions => self.overlay = Overlay::None,
+                _ => continue,
+            }
+            return;
+        }
+    }
+
+    /// Handle a key press in input mode, forwarding it to the running app.
+    fn handle_input_key(&mut self, key: KeyEvent) {
+        match self.input.handle_key(key) {
+            InputEvent::Editing => {}
+            InputEvent::Left => self.overlay = Overlay::None,
+            InputEvent::Send(bytes) => self.write_stdin(&bytes),
+        }
+    }
+
+    /// Write to the stdin of the running app.
+    ///
+    /// NOTE: Unless the app runs in a pseudo-terminal nothing echos what's
+    /// written back, so whole lines are shown in the app's tab to keep track.
+    fn write_stdin(&mut self, bytes: &[u8]) {
+        let (result, echoed) = match self.processes.lock() {
+            Ok(mut processes) => match processes.get_mut(APP) {
+                Some(process) => (process.write_stdin(bytes), process.is_pty()),
+                None => (
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::NotConnected,
+                        "no app is running",
+                    )),
+                    false,
+                ),
             },
-            _ => {}
+            Err(_) => return,
+        };
+
+        let Ok(mut tabs) = self.tabs.lock() else {
+            return;
+        };
+        let msg = match result {
+            Ok(_) if !echoed && bytes.ends_with(b"\n") && bytes.len() > 1 => {
+                let line = String::from_utf8_lossy(bytes).trim_end().to_string();
+                format!("\x1b[2m> {line}\x1b[0m")
+            }
+            Ok(_) => return,
+            Err(e) => Watcher::format_log_msg(&format!("failed to write to stdin: {e}")),
+        };
+        tabs.add_log(Tab::All, LogSource::Watcher, msg.clone());
+        tabs.add_log(Tab::App, LogSource::Watcher, msg);
+    }
+
+    /// Run a command entered in the command palette.
+    fn run_command(&mut self, input: &str) {
+        let command = match PaletteCommand::parse(input) {
+            Ok(command) => command,
+            Err(e) => {
+                self.with_display(|display| display.add_message(&e.message()));
+                return;
+            }
+        };
+
+        match command {
+            PaletteCommand::Rebuild => self.send_command(WatcherCommand::Rebuild),
+            PaletteCommand::Exec(command) => self.send_command(WatcherCommand::Exec(command)),
+            PaletteCommand::Test { only_failed } => {
+                self.send_command(WatcherCommand::RunTests { only_failed })
+            }
+            PaletteCommand::SetMode(mode) => self.send_command(WatcherCommand::SetMode(mode)),
+            PaletteCommand::Build => self.open_build_menu(),
+            PaletteCommand::SetInterval(seconds) => {
+                self.send_command(WatcherCommand::SetWatchInterval(seconds))
+            }
+            PaletteCommand::Clear => {
+                if let Ok(mut tabs) = self.tabs.lock() {
+                    let tab = tabs.selected();
+                    tabs.clear(tab);
+                }
+            }
+            PaletteCommand::Filter(filter) => {
+                self.with_display(|display| display.set_filter(filter));
+            }
+            PaletteCommand::Help => self.overlay = Overlay::Help { scroll: 0 },
+            PaletteCommand::Quit => {
+                self.perform(Action::Quit);
+            }
         }
+    }
 
-        Ok(())
+    /// Open the build menu, reading what can be picked from the package in the background.
+    fn open_build_menu(&mut self) {
+        self.build_menu.open();
+        self.overlay = Overlay::BuildMenu;
+    }
+
+    /// Send a command to the running `Watcher`.
+    fn send_command(&self, command: WatcherCommand) {
+        if self.commands.send(command).is_err() {
+            self.with_display(|display| display.add_message("the watcher isn't running"));
+        }
     }
 
-    /// Render the application title bar within the display interface.
+    /// Perform an action triggered by the user, returning
+    /// false if it doesn't apply to the current state of the `App`.
+    fn perform(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => {
+                self.running.store(false, Ordering::SeqCst);
+                self.shutdown();
+            }
+            Action::ScrollUp => {
+                self.with_display(Display::prev_row);
+            }
+            Action::ScrollDown => {
+                self.with_display(Display::next_row);
+            }
+            Action::HalfPageUp => {
+                self.with_display(Display::prev_half_page);
+            }
+            Action::HalfPageDown => {
+                self.with_display(Display::next_half_page);
+            }
+            Action::PageUp => {
+                self.with_display(Display::prev_page);
+            }
+            Action::PageDown => {
+                self.with_display(Display::next_page);
+            }
+            Action::Top => {
+                self.with_display(Display::first_row);
+            }
+            Action::Bottom => {
+                self.with_display(Display::last_row);
+            }
+            Action::ToggleFollow => {
+                self.with_display(Display::toggle_follow);
+            }
+            Action::NextTab => self.update_tabs(Tabs::select_next),
+            Action::PrevTab => self.update_tabs(Tabs::select_prev),
+            Action::SelectTab(idx) => {
+                let Some(tab) = Tab::ALL.get(idx) else {
+                    return false;
+                };
+                self.update_tabs(|tabs| tabs.select(*tab));
+            }
+            Action::CycleLayout => self.update_layout(PaneLayout::cycle_split),
+            Action::FocusNextPane => self.update_tabs(Tabs::focus_next_pane),
+            Action::GrowPane => self.update_layout(PaneLayout::grow_focused_pane),
+            Action::ShrinkPane => self.update_layout(PaneLayout::shrink_focused_pane),
+            Action::ToggleSelection => {
+                self.with_display(Display::toggle_selection);
+            }
+            Action::ToggleMouse => {
+                self.mouse_enabled = !self.mouse_enabled;
+                let msg = match (Self::capture_mouse(self.mouse_enabled), self.mouse_enabled) {
+                    (Err(e), _) => format!("failed to toggle the mouse: {e}"),
+                    (Ok(_), true) => String::from("mouse enabled"),
+                    (Ok(_), false) => String::from("mouse disabled, the terminal can select text"),
+                };
+                self.with_display(|display| display.add_message(&msg));
+            }
+            Action::ForwardInput | Action::ForwardRawInput => {
+                let pty = match self.processes.lock() {
+                    Ok(processes) => processes.get(APP).is_some_and(|process| process.is_pty()),
+                    Err(_) => false,
+                };
+                self.input.open(action == Action::ForwardRawInput, pty);
+                self.overlay = Overlay::Input;
+            }
+            Action::RerunFailedTests => {
+                self.send_command(WatcherCommand::RunTests { only_failed: true })
+            }
+            Action::OpenBuildMenu => self.open_build_menu(),
+            // NOTE: These only apply within the build menu.
+            Action::PickBuildOption | Action::ApplyBuildOptions | Action::DiscardBuildOptions => {
+                return false;
+            }
+            Action::CycleMode => {
+                if let Some(mode) = self.tabs.lock().ok().map(|tabs| tabs.mode.next()) {
+                    self.send_command(WatcherCommand::SetMode(mode));
+                }
+            }
+            Action::ShowHelp => self.overlay = Overlay::Help { scroll: 0 },
+            Action::OpenPalette => {
+                self.palette.open();
+                self.overlay = Overlay::Palette;
+            }
+            // The actions below only apply while selecting rows (visual mode)
+            _ if !self.is_selecting() => return false,
+            Action::CancelSelection => {
+                self.with_display(Display::cancel_selection);
+            }
+            Action::CopySelection => self.copy_selection(),
+            Action::SaveSelection => self.save_selection(),
+            Action::ToggleExportAnsi => {
+                self.with_display(Display::toggle_strip_ansi_on_export);
+            }
+        }
+
+        true
+    }
+
+    /// Update the tabs, remembering the pane layout for the next session.
+    fn update_tabs(&self, f: impl FnOnce(&mut Tabs)) {
+        if let Ok(mut tabs) = self.tabs.lock() {
+            f(&mut tabs);
+            tabs.layout.save();
+        }
+    }
+
+    /// Update the pane layout, remembering it for the next session.
+    fn update_layout(&self, f: impl FnOnce(&mut PaneLayout)) {
+        if let Ok(mut tabs) = self.tabs.lock() {
+            f(&mut tabs.layout);
+            tabs.layout.save();
+        }
+    }
+
+    /// Run a function on the display of the currently selected tab.
+    fn with_display<T>(&self, f: impl FnOnce(&mut Display) -> T) -> Option<T> {
+        let mut tabs = self.tabs.lock().ok()?;
+        Some(f(tabs.display()))
+    }
+
+    /// Is the user currently selecting rows (visual mode) in the display ?
+    fn is_selecting(&self) -> bool {
+        self.with_display(|display| display.is_selecting())
+            .unwrap_or_default()
+    }
+
+    /// Copy the currently selected rows to the clipboard, ending the selection.
+    fn copy_selection(&mut self) {
+        self.with_display(|display| {
+            let Some(text) = display.selected_text() else {
+                return;
+            };
+
+            let n_lines = text.lines().count();
+            display.cancel_selection();
+            match export::copy_to_clipboard(&text) {
+                Ok(_) => display.add_message(&format!("copied {n_lines} lines to the clipboard")),
+                Err(e) => {
+                    display.add_message(&format!("failed to copy selection to the clipboard: {e}"))
+                }
+            }
+        });
+    }
+
+    /// Save the currently selected rows to a file, ending the selection.
+    fn save_selection(&mut self) {
+        self.with_display(|display| {
+            let Some(text) = display.selected_text() else {
+                return;
+            };
+
+            display.cancel_selection();
+            match export::save_to_file(&text) {
+                Ok(path) => display.add_message(&format!("saved selection to {}", path.display())),
+                Err(e) => display.add_message(&format!("failed to save selection: {e}")),
+            }
+        });
+    }
+
+    /// Render the application title bar within the display interface, along with the mode
+    /// (unless running), the build options (unless cargo's defaults), the status of the
+    /// tests, and the app (and the other processes).
     fn render_title_bar(&self, area: Rect, buf: &mut Buffer) {
-        let layout = Layout::horizontal([Constraint::Min(0)]);
-        let [title_area] = layout.areas(area);
+        // NOTE: The title is centered in whatever space the statuses leave.
+        let status = self.tabs.lock().ok().and_then(|tabs| {
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            if tabs.mode != Mode::Run {
+                spans.push(Span::styled(
+                    format!(" {} mode ", tabs.mode.name()),
+                    theme().status,
+                ));
+            }
+            if !self.build_menu.applied().is_default() {
+                spans.push(Span::styled(
+                    format!(" {} ", self.build_menu.applied().summary()),
+                    theme().status,
+                ));
+            }
+            spans.extend(tabs.tests.span());
+            spans.extend(tabs.statuses.line().into_iter().flat_map(|line| line.spans));
+            (!spans.is_empty()).then(|| Line::from(spans))
+        });
+        let status_width = status.as_ref().map_or(0, |status| status.width() as u16);
+        let layout = Layout::horizontal([Constraint::Min(0), Constraint::Length(status_width)]);
+        let [title_area, status_area] = layout.areas(area);
 
-        let title_span = Span::styled(format!("Unlimited Ammo {VERSION}"), THEME.app_title);
+        let title_span = Span::styled(format!("Unlimited Ammo {VERSION}"), theme().app_title);
 
         Paragraph::new(title_span)
             .alignment(ratatui::layout::Alignment::Center)
             .render(title_area, buf);
+
+        // The mode, build options, and status of the tests and processes, on the right
+        if let Some(status) = status {
+            Paragraph::new(status)
+                .alignment(ratatui::layout::Alignment::Right)
+                .render(status_area, buf);
+        }
     }
 
-    /// Render the main display, which is a table of log messages captured
-    /// by the build/run processes triggered on file changes by `Watcher`.
-    fn render_selected_tab(&self, area: Rect, buf: &mut Buffer) {
-        // TODO: Handle this unwrap
-        let mut display = self.display.lock().unwrap();
-        display.render(area, buf);
+    /// Render the tab bar, listing each tab with its number key
+    /// and a marker when it has log messages the user hasn't seen.
+    fn render_tab_bar(&self, area: Rect, buf: &mut Buffer) {
+        let Ok(tabs) = self.tabs.lock() else {
+            return;
+        };
+
+        let mut x = area.x;
+        let spans: Vec<Span<'_>> = Tab::ALL
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, tab)| {
+                let style = match *tab == tabs.selected() {
+                    true => theme().tab_selected,
+                    false => theme().tab,
+                };
+                let marker = match tabs.is_unread(*tab) {
+                    true => Span::styled("●", theme().tab_unread.patch(style)),
+                    false => Span::styled(" ", style),
+                };
+                let title = Span::styled(format!(" {} {}", idx + 1, tab.title()), style);
+
+                // Remember where each tab is drawn so it can be clicked on
+                let width = (title.width() + 2) as u16;
+                self.buttons
+                    .borrow_mut()
+                    .push((Rect::new(x, area.y, width, 1), Action::SelectTab(idx)));
+                x += width;
+
+                [title, marker, Span::raw(" ")]
+            })
+            .collect();
+
+        Line::from(spans).render(area, buf);
+    }
+
+    /// Render the panes, each displaying the table of log messages of a tab
+    /// captured by the build/run processes triggered on file changes by `Watcher`.
+    fn render_panes(&self, area: Rect, buf: &mut Buffer) {
+        let Ok(mut tabs) = self.tabs.lock() else {
+            return;
+        };
+        let layout = tabs.layout.clone();
+
+        let first_pane = Constraint::Percentage(layout.first_pane_percent);
+        let second_pane = Constraint::Percentage(100 - layout.first_pane_percent);
+        let pane_areas = match layout.split {
+            Split::Single => {
+                let display = tabs.display_of(layout.panes[layout.focused]);
+                display.render(area, buf);
+                return;
+            }
+            Split::Horizontal => Layout::horizontal([first_pane, second_pane]).split(area),
+            Split::Vertical => Layout::vertical([first_pane, second_pane]).split(area),
+        };
+
+        for (pane, pane_area) in pane_areas.iter().enumerate() {
+            let [header_area, display_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(*pane_area);
+            let tab = layout.panes[pane];
+
+            let header_style = match pane == layout.focused {
+                true => theme().pane_header_focused,
+                false => theme().pane_header,
+            };
+            Line::from(format!(" {} {} ", tab.index() + 1, tab.title()))
+                .style(header_style)
+                .render(header_area, buf);
+
+            tabs.display_of(tab).render(display_area, buf);
+        }
     }
 
-    /// Render the command bar within the display interface.
-    fn render_command_bar(area: Rect, buf: &mut Buffer) {
-        let keys = [("K/↑", "Up"), ("J/↓", "Down"), ("Q/Esc", "Quit")];
+    /// Render the command bar within the display interface, listing
+    /// the keys of the active keymap for the most common actions.
+    ///
+    /// NOTE: Entries that don't fit within the width are left out.
+    fn render_command_bar(&self, area: Rect, buf: &mut Buffer) {
+        let entries: &[(&[Action], &str)] = if self.is_selecting() {
+            &[
+                (&[Action::ScrollUp, Action::ScrollDown], "Move"),
+                (&[Action::CopySelection], "Copy"),
+                (&[Action::SaveSelection], "Save"),
+                (&[Action::ToggleExportAnsi], "Toggle ANSI"),
+                (&[Action::CancelSelection], "Cancel"),
+            ]
+        } else {
+            &[
+                (&[Action::ScrollUp, Action::ScrollDown], "Scroll"),
+                (&[Action::Top, Action::Bottom], "Top/End"),
+                (&[Action::ToggleFollow], "Follow"),
+                (&[Action::NextTab], "Tab"),
+                (&[Action::CycleLayout], "Layout"),
+                (&[Action::ToggleSelection], "Select"),
+                (&[Action::OpenPalette], "Command"),
+                (&[Action::ShowHelp], "Help"),
+                (&[Action::Quit], "Quit"),
+            ]
+        };
 
-        let spans: Vec<Span<'_>> = keys
+        let mut width = 0;
+        let buttons: Vec<(Action, [Span<'_>; 2])> = entries
             .iter()
-            .flat_map(|(key, desc)| {
-                let key = Span::styled(format!(" {key} "), THEME.key_binding.key);
-                let desc = Span::styled(format!(" {desc} "), THEME.key_binding.description);
-                [key, desc]
+            .filter_map(|(actions, desc)| {
+                Some((actions[0], self.keymap.short_label(actions)?, desc))
+            })
+            .map(|(action, key, desc)| {
+                let key = Span::styled(format!(" {key} "), theme().key_binding.key);
+                let desc = Span::styled(format!(" {desc} "), theme().key_binding.description);
+                (action, [key, desc])
+            })
+            .take_while(|(_, [key, desc])| {
+                width += key.width() + desc.width();
+                width <= area.width as usize
             })
             .collect();
 
-        // TODO: Stylize using theme.rs
+        // Remember where each button is drawn so it can be clicked on,
+        // the line is centered so they start wherever it starts.
+        let line_width: usize = buttons
+            .iter()
+            .map(|(_, [key, desc])| key.width() + desc.width())
+            .sum();
+        let mut x = area.x + (area.width.saturating_sub(line_width as u16)) / 2;
+        for (action, [key, desc]) in &buttons {
+            let button_width = (key.width() + desc.width()) as u16;
+            self.buttons
+                .borrow_mut()
+                .push((Rect::new(x, area.y, button_width, 1), *action));
+            x += button_width;
+        }
+
+        let spans: Vec<Span<'_>> = buttons.into_iter().flat_map(|(_, spans)| spans).collect();
         Line::from(spans)
             .centered()
-            .style((Color::Indexed(236), Color::Indexed(232)))
+            .style(theme().command_bar)
             .render(area, buf);
     }
 
+    /// Get the lines of the help screen, listing the keys bound to
+    /// each action in the active keymap and the palette commands.
+    fn help_lines(&self) -> Vec<Line<'static>> {
+        let key_entries = self.keymap.help_entries();
+        let command_entries: Vec<(String, String)> = PaletteCommand::ALL
+            .iter()
+            .map(|(name, args, description)| {
+                (
+                    format!(":{name} {args}").trim_end().to_string(),
+                    description.to_string(),
+                )
+            })
+            .collect();
+
+        let width = key_entries
+            .iter()
+            .chain(&command_entries)
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or_default();
+        let entry_line = |(key, description): &(String, String)| {
+            Line::from(vec![
+                Span::styled(format!(" {key:<width$} "), theme().key_binding.key),
+                Span::raw(format!("  {description}")),
+            ])
+        };
+
+        let mut lines = vec![Line::styled("Keys", theme().app_title)];
+        lines.extend(key_entries.iter().map(entry_line));
+        lines.push(Line::default());
+        lines.push(Line::styled("Commands", theme().app_title));
+        lines.extend(command_entries.iter().map(entry_line));
+
+        lines
+    }
+
+    /// Render the help screen over the interface.
+    fn render_help(&self, area: Rect, buf: &mut Buffer, scroll: usize) {
+        let width = area.width.saturating_sub(4).min(80);
+        let height = area.height.saturating_sub(2);
+        let help_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        Clear.render(help_area, buf);
+        Paragraph::new(self.help_lines())
+            .scroll((scroll as u16, 0))
+            .style(theme().root)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title(" Help ")
+                    .title_style(theme().app_title),
+            )
+            .render(help_area, buf);
+    }
+
     /// Handle the cleaning up of the application before shutdown.
     fn shutdown(&mut self) {
-        if let Ok(mut build_process) = self.current_build_process.lock() {
-            if let Some(process) = build_process.as_mut() {
+        // NOTE: Taking the processes out lets the watcher know they
+        // were killed, instead of restarting them as if they crashed.
+        if let Ok(mut processes) = self.processes.lock() {
+            for mut process in processes.take_all() {
                 // TODO: Handle logging of failing to kill build process
                 let _ = process.kill();
+                let _ = process.wait();
             }
         }
     }
@@ -161,15 +911,26 @@ impl Widget for &App {
     /// Render the application
     fn render(self, area: Rect, buf: &mut Buffer) {
         let vertical = Layout::vertical([
+            Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Min(0),
             Constraint::Length(1),
         ]);
-        let [title_bar, tab, bottom_bar] = vertical.areas(area);
+        let [title_bar, tab_bar, tab, bottom_bar] = vertical.areas(area);
 
-        Block::new().style(THEME.root).render(area, buf);
+        Block::new().style(theme().root).render(area, buf);
+        self.buttons.borrow_mut().clear();
         self.render_title_bar(title_bar, buf);
-        self.render_selected_tab(tab, buf);
-        App::render_command_bar(bottom_bar, buf);
+        self.render_tab_bar(tab_bar, buf);
+        self.render_panes(tab, buf);
+        self.render_command_bar(bottom_bar, buf);
+
+        match self.overlay {
+            Overlay::None => {}
+            Overlay::Help { scroll } => self.render_help(area, buf, scroll),
+            Overlay::Palette => self.palette.render(bottom_bar, buf),
+            Overlay::Input => self.input.render(bottom_bar, buf),
+            Overlay::BuildMenu => self.build_menu.render(area, buf, &self.keymap),
+        }
     }
 }
@@ -0,0 +1,265 @@
+use crate::{
+    build_options::{BuildChoices, BuildOptions, RunTarget},
+    interface::{Action, Keymap, theme},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Widget},
+};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+/// Something which can be picked in the build menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MenuItem {
+    /// Build with the release profile, or not.
+    Release,
+
+    /// Enable a feature of the package, or not.
+    Feature(String),
+
+    /// Build and run a binary (or example), `None` for cargo's default.
+    RunTarget(Option<RunTarget>),
+
+    /// Build for a target triple, `None` for the host.
+    TargetTriple(Option<String>),
+}
+
+/// The build menu, picking the options (profile, features, target)
+/// the next builds are ran with, applied once it's closed.
+#[derive(Debug, Default)]
+pub struct BuildMenu {
+    /// What can be picked, read from the package when the menu is opened.
+    choices: BuildChoices,
+
+    /// The options being picked.
+    options: BuildOptions,
+
+    /// The options the builds are currently ran with.
+    applied: BuildOptions,
+
+    /// The position of the item under the cursor.
+    cursor: usize,
+
+    /// Why the choices couldn't be read, if they couldn't.
+    error: Option<String>,
+
+    /// The choices being read in the background, if they are.
+    loading: Option<Receiver<Result<BuildChoices, String>>>,
+}
+impl BuildMenu {
+    /// Open the menu, reading what can be picked from the package
+    /// in the background (see `receive_choices`).
+    ///
+    /// NOTE: The choices are read every time, so new features or
+    /// binaries show up without restarting, the ones read last are
+    /// shown meanwhile as reading them takes a while (`cargo metadata`).
+    pub fn open(&mut self) {
+        self.options = self.applied.clone();
+        if self.loading.is_some() {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(BuildChoices::load().map_err(|e| e.to_string()));
+        });
+        self.loading = Some(receiver);
+    }
+
+    /// Receive the choices read in the background, if they were.
+    pub fn receive_choices(&mut self) {
+        let Some(loading) = &self.loading else {
+            return;
+        };
+        let result = match loading.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(String::from("failed to read the choices")),
+        };
+
+        match result {
+            Ok(choices) => {
+                self.choices = choices;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+        self.loading = None;
+        self.cursor = self.cursor.min(self.items().len().saturating_sub(1));
+    }
+
+    /// Close the menu, returning the options picked if they changed.
+    pub fn close(&mut self) -> Option<BuildOptions> {
+        if self.options == self.applied {
+            return None;
+        }
+
+        self.applied = self.options.clone();
+        Some(self.applied.clone())
+    }
+
+    /// Get the options the builds are currently ran with.
+    pub fn applied(&self) -> &BuildOptions {
+        &self.applied
+    }
+
+    /// Get the items which can be picked, under the title of their section.
+    fn items(&self) -> Vec<(&'static str, MenuItem)> {
+        let mut items = vec![("Profile", MenuItem::Release)];
+        items.extend(
+            self.choices
+                .features
+                .iter()
+                .map(|feature| ("Features", MenuItem::Feature(feature.clone()))),
+        );
+        if !self.choices.run_targets.is_empty() {
+            items.push(("Run", MenuItem::RunTarget(None)));
+            items.extend(
+                self.choices
+                    .run_targets
+                    .iter()
+                    .map(|run_target| ("Run", MenuItem::RunTarget(Some(run_target.clone())))),
+            );
+        }
+        items.push(("Target", MenuItem::TargetTriple(None)));
+        items.extend(
+            self.choices
+                .target_triples
+                .iter()
+                .map(|triple| ("Target", MenuItem::TargetTriple(Some(triple.clone())))),
+        );
+
+        items
+    }
+
+    /// Move the cursor up (negative) or down a number of items, stopping at the ends.
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.items().len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    /// Pick the item under the cursor, or unpick it (ex: a feature).
+    pub fn toggle(&mut self) {
+        let Some((_, item)) = self.items().into_iter().nth(self.cursor) else {
+            return;
+        };
+
+        match item {
+            MenuItem::Release => self.options.release = !self.options.release,
+            MenuItem::Feature(feature) => {
+                if !self.options.features.remove(&feature) {
+                    self.options.features.insert(feature);
+                }
+            }
+            MenuItem::RunTarget(run_target) => self.options.run_target = run_target,
+            MenuItem::TargetTriple(triple) => self.options.target_triple = triple,
+        }
+    }
+
+    /// Get how an item is shown, with a mark when it's picked.
+    fn item_label(&self, item: &MenuItem) -> String {
+        let (picked, label) = match item {
+            MenuItem::Release => (self.options.release, String::from("release (--release)")),
+            MenuItem::Feature(feature) => {
+                (self.options.features.contains(feature), feature.clone())
+            }
+            MenuItem::RunTarget(run_target) => (
+                self.options.run_target == *run_target,
+                match run_target {
+                    None => String::from("default"),
+                    Some(RunTarget::Bin(name)) => format!("bin {name}"),
+                    Some(RunTarget::Example(name)) => format!("example {name}"),
+                },
+            ),
+            MenuItem::TargetTriple(triple) => (
+                self.options.target_triple == *triple,
+                triple.clone().unwrap_or(String::from("host")),
+            ),
+        };
+
+        let mark = match (item, picked) {
+            (MenuItem::Release | MenuItem::Feature(_), true) => "[x]",
+            (MenuItem::Release | MenuItem::Feature(_), false) => "[ ]",
+            (_, true) => "(•)",
+            (_, false) => "( )",
+        };
+
+        format!("{mark} {label}")
+    }
+
+    /// Render the menu in a popup over the interface, scrolled
+    /// to keep the item under the cursor in view.
+    pub fn render(&self, area: Rect, buf: &mut Buffer, keymap: &Keymap) {
+        let mut lines = Vec::new();
+        let mut cursor_line = 0;
+        let mut section = "";
+        for (idx, (item_section, item)) in self.items().iter().enumerate() {
+            if *item_section != section {
+                section = item_section;
+                lines.push(Line::styled(format!(" {section}"), theme().app_title));
+            }
+            if idx == self.cursor {
+                cursor_line = lines.len();
+            }
+
+            let style = match idx == self.cursor {
+                true => theme().selection,
+                false => theme().root,
+            };
+            lines.push(Line::styled(format!("   {}", self.item_label(item)), style));
+        }
+        if self.loading.is_some() {
+            lines.push(Line::default());
+            let loading = " Loading the choices...";
+            lines.push(Line::styled(loading, theme().status_warning));
+        }
+        if let Some(error) = &self.error {
+            lines.push(Line::default());
+            lines.extend(
+                error
+                    .lines()
+                    .map(|line| Line::styled(format!(" {line}"), theme().status_error)),
+            );
+        }
+        lines.push(Line::default());
+        let footer = [
+            (Action::PickBuildOption, "pick"),
+            (Action::ApplyBuildOptions, "apply to the next build"),
+            (Action::DiscardBuildOptions, "discard"),
+        ];
+        lines.push(Line::from_iter(footer.into_iter().flat_map(
+            |(action, desc)| match keymap.short_label(&[action]) {
+                Some(key) => vec![
+                    Span::styled(format!(" {key} "), theme().key_binding.key),
+                    Span::styled(format!(" {desc}  "), theme().key_binding.description),
+                ],
+                None => vec![],
+            },
+        )));
+
+        let width = area.width.saturating_sub(4).min(60);
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(2));
+        let menu_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let inner_height = height.saturating_sub(2) as usize;
+        let scroll = (cursor_line + 1).saturating_sub(inner_height);
+
+        Clear.render(menu_area, buf);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .style(theme().root)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title(" Build options ")
+                    .title_style(theme().app_title),
+            )
+            .render(menu_area, buf);
+    }
+}
@@ -1,23 +1,73 @@
+use crate::{ansi, event::LogSource, interface::theme, watcher::Watcher};
 use ansi_to_tui::IntoText;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Margin, Rect},
+    text::Span,
     widgets::{
-        Block, Cell, Clear, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
-        Table, TableState, Widget,
+        Block, Cell, Clear, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
+        StatefulWidget, Table, TableState, Widget,
     },
 };
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use unicode_segmentation::UnicodeSegmentation;
 
+/// How many rows a turn of the mouse wheel scrolls.
+const WHEEL_SCROLL_ROWS: usize = 3;
+
+/// A log message in the display, and where it originated from.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub source: LogSource,
+    pub text: String,
+}
+
+/// Which log messages to show in the display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Only log messages from a source (ex: `stderr`).
+    Source(LogSource),
+
+    /// Only log messages containing some text (ignoring case and colors).
+    Text(String),
+}
+impl Filter {
+    /// Parse a filter, a log source name or otherwise some text to look for.
+    pub fn parse(filter: &str) -> Self {
+        match LogSource::from_name(filter) {
+            Some(source) => Filter::Source(source),
+            None => Filter::Text(filter.to_lowercase()),
+        }
+    }
+
+    /// Should the log message be shown in the display ?
+    fn matches(&self, log: &LogEntry) -> bool {
+        match self {
+            Filter::Source(source) => log.source == *source,
+            Filter::Text(text) => ansi::strip(&log.text).to_lowercase().contains(text),
+        }
+    }
+
+    /// Get a label of the filter to show the user.
+    fn label(&self) -> String {
+        match self {
+            Filter::Source(source) => source.name().to_string(),
+            Filter::Text(text) => format!("\"{text}\""),
+        }
+    }
+}
+
 #[derive(Default, Clone, Debug)]
 /// The display interface for the table
 /// of log messages produced from `Watcher`
 /// building/running the project on changes.
 pub struct Display {
     /// The current log messages in display
-    pub logs: Arc<Mutex<Vec<String>>>,
+    pub logs: Arc<Mutex<Vec<LogEntry>>>,
+
+    /// Which log messages to show, all of them when `None`.
+    pub filter: Option<Filter>,
 
     /// The current state of the table which
     /// contains the log messages being displayed
@@ -43,10 +93,49 @@ pub struct Display {
     /// log message row in the table.
     ///
     /// NOTE: This is set to true after each new
-    /// log is added, then set back to false after
-    /// jumping to the latest log row.
+    /// log is added while following, then set back
+    /// to false after jumping to the latest log row.
     pub jump_to_latest: bool,
 
+    /// Is the display following (tailing) the latest log ?
+    ///
+    /// NOTE: This is disabled as soon as the user scrolls
+    /// up, so reading older logs isn't interrupted by new ones.
+    pub follow: bool,
+
+    /// The number of log lines added since the display
+    /// stopped following the latest log.
+    pub n_unseen_lines: usize,
+
+    /// The number of log rows visible in the display table
+    /// during the last render, used for page navigation.
+    pub page_height: usize,
+
+    /// Where the current selection (visual mode) started,
+    /// as a row in the display table.
+    ///
+    /// NOTE: The selection spans from here to the
+    /// currently selected row, `None` when not selecting.
+    pub selection_anchor: Option<usize>,
+
+    /// Should ANSI escape codes be stripped from
+    /// selections when copying or saving them ?
+    pub strip_ansi_on_export: bool,
+
+    /// Which log message (and line within it) each row
+    /// in the display table came from, as of the last render.
+    pub visual_row_map: Vec<(usize, usize)>,
+
+    /// Which column of its log line each row in the display
+    /// table starts at, as of the last render.
+    ///
+    /// NOTE: This is 0 unless a long log line was wrapped.
+    pub visual_row_offsets: Vec<usize>,
+
+    /// Where the display was drawn during the last render,
+    /// used to find what was clicked on with the mouse.
+    pub area: Rect,
+
     /// Does the display need to be redrew ?
     pub needs_redraw: Arc<AtomicBool>,
 }
@@ -57,22 +146,51 @@ impl Display {
         state.select(Some(0));
         Self {
             logs: Arc::new(Mutex::new(Vec::new())),
+            filter: None,
             needs_redraw: Arc::new(AtomicBool::new(false)),
             selected_visual_idx: 0,
             n_visual_rows: 0,
             jump_to_latest: false,
+            follow: true,
+            n_unseen_lines: 0,
+            page_height: 0,
+            selection_anchor: None,
+            strip_ansi_on_export: true,
+            visual_row_map: Vec::new(),
+            visual_row_offsets: Vec::new(),
+            area: Rect::default(),
             state,
         }
     }
 
     /// Add a log message to the display
-    pub fn add_log(&mut self, log: String) {
+    pub fn add_log(&mut self, source: LogSource, log: String) {
         let mut logs = self.logs.lock().unwrap();
-        logs.push(log);
 
-        // Jump to the most recent log, which is
-        // this log we just added to the display
-        self.jump_to_latest = true;
+        // Jump to the most recent log, which is this log we're
+        // adding to the display, unless the user is scrolling
+        if self.follow {
+            self.jump_to_latest = true;
+        } else {
+            self.n_unseen_lines += log.lines().count().max(1);
+        }
+
+        logs.push(LogEntry { source, text: log });
+    }
+
+    /// Add a message from Unlimited Ammo itself to the display
+    /// (ex: the outcome of something the user did).
+    pub fn add_message(&mut self, msg: &str) {
+        self.add_log(LogSource::Watcher, Watcher::format_log_msg(msg));
+    }
+
+    /// Only show the log messages matching a filter, or all of them.
+    ///
+    /// NOTE: This ends any selection, since the rows it covered move.
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+        self.selection_anchor = None;
+        self.set_follow(true);
     }
 
     /// Trigger a redraw of the display
@@ -87,21 +205,216 @@ impl Display {
 
     /// Go to the next log message in the display table.
     pub fn next_row(&mut self) {
+        self.scroll_down(1);
+    }
+
+    /// Go to the previous log message in the display table.
+    pub fn prev_row(&mut self) {
+        self.scroll_up(1);
+    }
+
+    /// Scroll down a page of log messages in the display table.
+    pub fn next_page(&mut self) {
+        self.scroll_view_down(self.page_height.max(1));
+    }
+
+    /// Scroll up a page of log messages in the display table.
+    pub fn prev_page(&mut self) {
+        self.scroll_view_up(self.page_height.max(1));
+    }
+
+    /// Scroll down half a page of log messages in the display table.
+    pub fn next_half_page(&mut self) {
+        self.scroll_view_down((self.page_height / 2).max(1));
+    }
+
+    /// Scroll up half a page of log messages in the display table.
+    pub fn prev_half_page(&mut self) {
+        self.scroll_view_up((self.page_height / 2).max(1));
+    }
+
+    /// Go to the first (oldest) log message in the display table.
+    pub fn first_row(&mut self) {
+        self.follow = false;
+        self.selected_visual_idx = 0;
+    }
+
+    /// Go to the last (latest) log message in the display table,
+    /// which also starts following new log messages again.
+    pub fn last_row(&mut self) {
+        self.set_follow(true);
+    }
+
+    /// Toggle following (tailing) the latest log message.
+    pub fn toggle_follow(&mut self) {
+        self.set_follow(!self.follow);
+    }
+
+    /// Start or stop following (tailing) the latest log message.
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        if follow {
+            self.n_unseen_lines = 0;
+            self.jump_to_latest = true;
+        }
+    }
+
+    /// Scroll down a few rows with the mouse wheel.
+    pub fn wheel_down(&mut self) {
+        self.scroll_view_down(WHEEL_SCROLL_ROWS);
+    }
+
+    /// Scroll up a few rows with the mouse wheel.
+    pub fn wheel_up(&mut self) {
+        self.scroll_view_up(WHEEL_SCROLL_ROWS);
+    }
+
+    /// Get the row in the display table at a position on the
+    /// screen (ex: clicked on with the mouse), as of the last render.
+    pub fn row_at(&self, x: u16, y: u16) -> Option<usize> {
+        if !self.area.contains(ratatui::layout::Position { x, y }) {
+            return None;
+        }
+
+        let row = self.state.offset() + (y - self.area.y) as usize;
+        (row < self.n_visual_rows).then_some(row)
+    }
+
+    /// Select a row in the display table (ex: clicked on with the mouse).
+    ///
+    /// NOTE: Selecting the last row starts following again.
+    pub fn select_row(&mut self, row: usize) {
+        self.selected_visual_idx = row.min(self.n_visual_rows.saturating_sub(1));
+        if self.selected_visual_idx + 1 == self.n_visual_rows {
+            self.set_follow(true);
+        } else {
+            self.follow = false;
+        }
+    }
+
+    /// Get the log line (without ANSI escape codes) at a position on
+    /// the screen, along with the column of the line it's at.
+    pub fn line_at(&self, x: u16, y: u16) -> Option<(String, usize)> {
+        let row = self.row_at(x, y)?;
+        let (log_idx, line_idx) = *self.visual_row_map.get(row)?;
+        let offset = self
+            .visual_row_offsets
+            .get(row)
+            .copied()
+            .unwrap_or_default();
+
+        let logs = self.logs.lock().ok()?;
+        let line = logs.get(log_idx)?.text.lines().nth(line_idx)?;
+
+        Some((ansi::strip(line), offset + (x - self.area.x) as usize))
+    }
+
+    /// Scroll the view and selection down `n` rows in the display table.
+    fn scroll_view_down(&mut self, n: usize) {
+        let max_offset = self.n_visual_rows.saturating_sub(self.page_height);
+        let offset = (self.state.offset() + n).min(max_offset);
+        *self.state.offset_mut() = offset;
+        self.scroll_down(n);
+    }
+
+    /// Scroll the view and selection up `n` rows in the display table.
+    fn scroll_view_up(&mut self, n: usize) {
+        let offset = self.state.offset().saturating_sub(n);
+        *self.state.offset_mut() = offset;
+        self.scroll_up(n);
+    }
+
+    /// Scroll down `n` rows in the display table.
+    ///
+    /// NOTE: Reaching the last row starts following again.
+    fn scroll_down(&mut self, n: usize) {
         if self.n_visual_rows == 0 {
             return;
         }
 
-        self.selected_visual_idx = (self.selected_visual_idx + 1) % self.n_visual_rows;
+        let last_idx = self.n_visual_rows - 1;
+        self.selected_visual_idx = (self.selected_visual_idx + n).min(last_idx);
+        if self.selected_visual_idx == last_idx {
+            self.set_follow(true);
+        }
     }
 
-    /// Go to the previous log message in the display table.
-    pub fn prev_row(&mut self) {
+    /// Scroll up `n` rows in the display table.
+    ///
+    /// NOTE: This stops following the latest log message.
+    fn scroll_up(&mut self, n: usize) {
         if self.n_visual_rows == 0 {
             return;
         }
 
-        self.selected_visual_idx =
-            (self.selected_visual_idx + self.n_visual_rows - 1) % self.n_visual_rows;
+        self.follow = false;
+        self.selected_visual_idx = self.selected_visual_idx.saturating_sub(n);
+    }
+
+    /// Start selecting rows (visual mode) from the currently
+    /// selected row, or stop selecting if already selecting.
+    ///
+    /// NOTE: This stops following the latest log message, so
+    /// new log messages don't move the selection around.
+    pub fn toggle_selection(&mut self) {
+        self.selection_anchor = match self.selection_anchor {
+            Some(_) => None,
+            None => {
+                self.follow = false;
+                Some(self.selected_visual_idx)
+            }
+        };
+    }
+
+    /// Stop selecting rows (visual mode).
+    pub fn cancel_selection(&mut self) {
+        self.selection_anchor = None;
+    }
+
+    /// Is the user currently selecting rows (visual mode) ?
+    pub fn is_selecting(&self) -> bool {
+        self.selection_anchor.is_some()
+    }
+
+    /// Toggle stripping ANSI escape codes when exporting selections.
+    pub fn toggle_strip_ansi_on_export(&mut self) {
+        self.strip_ansi_on_export = !self.strip_ansi_on_export;
+    }
+
+    /// Get the range (inclusive) of selected rows in the display table.
+    pub fn selection_range(&self) -> Option<(usize, usize)> {
+        let anchor = self.selection_anchor?;
+        Some((
+            anchor.min(self.selected_visual_idx),
+            anchor.max(self.selected_visual_idx),
+        ))
+    }
+
+    /// Get the text of the log lines covered by the current selection.
+    ///
+    /// NOTE: Wrapped rows are joined back into their original log
+    /// line, so a selection always exports whole log lines.
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection_range()?;
+        let logs = self.logs.lock().ok()?;
+
+        let mut lines: Vec<(usize, usize)> = self
+            .visual_row_map
+            .get(start..=end.min(self.visual_row_map.len().saturating_sub(1)))?
+            .to_vec();
+        lines.dedup();
+
+        let text = lines
+            .into_iter()
+            .filter_map(|(log_idx, line_idx)| logs.get(log_idx)?.text.lines().nth(line_idx))
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        if self.strip_ansi_on_export {
+            Some(ansi::strip(&text))
+        } else {
+            Some(text)
+        }
     }
 
     /// Render the display
@@ -119,14 +432,29 @@ impl Display {
         // displayed within.
         let logs = self.logs.lock().unwrap();
         let mut visual_rows = vec![];
-        let mut visual_idx_map = vec![];
+        let mut visual_row_map = vec![];
+        let mut visual_row_offsets = vec![];
+
+        for (log_idx, log) in logs.iter().enumerate() {
+            if self
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !filter.matches(log))
+            {
+                continue;
+            }
 
-        for (log_idx, raw_log) in logs.iter().enumerate() {
-            let text = raw_log.into_text().unwrap_or_default();
+            let raw_log = &log.text;
+            let text = match theme().log_colors {
+                true => raw_log.into_text(),
+                false => ansi::strip(raw_log).into_text(),
+            }
+            .unwrap_or_default();
 
-            for line in text.lines {
+            for (line_idx, line) in text.lines.into_iter().enumerate() {
                 let mut current_line = ratatui::text::Line::default();
                 let mut current_width = 0;
+                let mut row_offset = 0;
 
                 for span in line.spans {
                     let content = span.content;
@@ -139,7 +467,9 @@ impl Display {
                         // then split it up into multiple display table rows
                         if current_width + g_width > available_width && current_width > 0 {
                             visual_rows.push(Row::new(vec![Cell::from(current_line.clone())]));
-                            visual_idx_map.push((log_idx, visual_rows.len()));
+                            visual_row_map.push((log_idx, line_idx));
+                            visual_row_offsets.push(row_offset);
+                            row_offset += current_width;
                             current_line = ratatui::text::Line::default();
                             current_width = 0;
                         }
@@ -154,30 +484,49 @@ impl Display {
 
                 if !current_line.spans.is_empty() {
                     visual_rows.push(Row::new(vec![Cell::from(current_line.clone())]));
-                    visual_idx_map.push((log_idx, visual_rows.len()));
+                    visual_row_map.push((log_idx, line_idx));
+                    visual_row_offsets.push(row_offset);
                 }
             }
         }
 
         // Update the visual rows being displayed
         self.n_visual_rows = visual_rows.len();
+        self.visual_row_map = visual_row_map;
+        self.visual_row_offsets = visual_row_offsets;
+        self.area = area;
+        self.page_height = area.height as usize;
         if self.jump_to_latest {
             self.selected_visual_idx = self.n_visual_rows.saturating_sub(1);
             self.jump_to_latest = false;
         }
+        self.selected_visual_idx = self
+            .selected_visual_idx
+            .min(self.n_visual_rows.saturating_sub(1));
+
+        // Highlight the rows in the current selection (visual mode)
+        if let Some((start, end)) = self.selection_range() {
+            visual_rows = visual_rows
+                .into_iter()
+                .enumerate()
+                .map(|(idx, row)| match idx >= start && idx <= end {
+                    true => row.style(theme().selection),
+                    false => row,
+                })
+                .collect();
+        }
 
         // Create and render the display table
-        let mut table_state = TableState::default();
-        table_state.select(Some(self.selected_visual_idx));
+        self.state.select(Some(self.selected_visual_idx));
 
         StatefulWidget::render(
-            Table::new(visual_rows, [Constraint::Percentage(100)]).row_highlight_style(
-                ratatui::style::Style::default().bg(ratatui::style::Color::DarkGray),
-            ),
+            Table::new(visual_rows, [Constraint::Percentage(100)])
+                .row_highlight_style(theme().highlight),
             area,
             buf,
-            &mut table_state,
+            &mut self.state,
         );
+        drop(logs);
 
         // Handle the display tables scroll bar
         let mut scrollbar_state = ScrollbarState::default()
@@ -197,6 +546,66 @@ impl Display {
             .end_symbol(None)
             .track_symbol(None)
             .thumb_symbol("▌")
+            .thumb_style(theme().scrollbar)
             .render(scrollbar_area, buf, &mut scrollbar_state);
+
+        self.render_follow_indicator(area, buf);
+        self.render_filter_indicator(area, buf);
+    }
+
+    /// Render an indicator in the bottom right corner of the display
+    /// when it's not following the latest log message.
+    fn render_follow_indicator(&self, area: Rect, buf: &mut Buffer) {
+        if (self.follow && !self.is_selecting()) || area.height == 0 {
+            return;
+        }
+
+        let text = if let Some((start, end)) = self.selection_range() {
+            let ansi = if self.strip_ansi_on_export {
+                "stripped"
+            } else {
+                "kept"
+            };
+            format!(" VISUAL {} rows (ANSI {ansi}) ", end - start + 1)
+        } else {
+            match self.n_unseen_lines {
+                0 => String::from(" paused "),
+                1 => String::from(" 1 new line "),
+                n => format!(" {n} new lines "),
+            }
+        };
+        let width = (text.chars().count() as u16).min(area.width);
+        let indicator_area = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.bottom().saturating_sub(1),
+            width,
+            height: 1,
+        };
+
+        Clear.render(indicator_area, buf);
+        Paragraph::new(Span::styled(text, theme().follow_indicator)).render(indicator_area, buf);
+    }
+
+    /// Render an indicator in the top right corner of the display
+    /// when only the log messages matching a filter are shown.
+    fn render_filter_indicator(&self, area: Rect, buf: &mut Buffer) {
+        let Some(filter) = &self.filter else {
+            return;
+        };
+        if area.height == 0 {
+            return;
+        }
+
+        let text = format!(" filter: {} ", filter.label());
+        let width = (text.chars().count() as u16).min(area.width);
+        let indicator_area = Rect {
+            x: area.right().saturating_sub(width),
+            y: area.y,
+            width,
+            height: 1,
+        };
+
+        Clear.render(indicator_area, buf);
+        Paragraph::new(Span::styled(text, theme().follow_indicator)).render(indicator_area, buf);
     }
 }
@@ -0,0 +1,110 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// A location in a file of the project (ex: `src/main.rs:12:5`),
+/// as found in compiler diagnostics, panics, and backtraces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+impl Location {
+    /// Parse a location (ex: `src/main.rs:12:5`), as long as the file exists.
+    fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split(':');
+        let path = PathBuf::from(parts.next()?);
+        let line = parts.next()?.parse().ok().filter(|line| *line > 0)?;
+        let column = parts.next().and_then(|column| column.parse().ok());
+
+        path.is_file().then_some(Self { path, line, column })
+    }
+
+    /// Find the location at a column (in characters) within a line of text.
+    pub fn find_at(text: &str, column: usize) -> Option<Self> {
+        let mut start = 0;
+        for word in text.split(' ') {
+            let end = start + word.chars().count();
+            if (start..end).contains(&column) {
+                let trimmed = word.trim_matches(|c: char| "()[]<>{},;'\"`:".contains(c));
+                return Self::parse(trimmed);
+            }
+            start = end + 1;
+        }
+
+        None
+    }
+}
+
+/// The editor to open locations in, from the `VISUAL` or `EDITOR`
+/// environment variables (ex: `nvim`, `code -w`), `vi` if neither are set.
+#[derive(Debug, Clone)]
+pub struct Editor {
+    program: String,
+    args: Vec<String>,
+}
+impl Editor {
+    /// Get the users editor.
+    pub fn from_env() -> Self {
+        let editor = ["VISUAL", "EDITOR"]
+            .iter()
+            .filter_map(std::env::var_os)
+            .map(|editor| editor.to_string_lossy().to_string())
+            .find(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| String::from("vi"));
+
+        let mut words = editor.split_whitespace().map(String::from);
+        Self {
+            program: words.next().unwrap_or_else(|| String::from("vi")),
+            args: words.collect(),
+        }
+    }
+
+    /// Get the name of the editor program (ex: `nvim` for `/usr/bin/nvim`).
+    fn name(&self) -> &str {
+        Path::new(&self.program)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&self.program)
+    }
+
+    /// Does the editor run in the terminal, so the interface
+    /// needs to step aside while it's open ?
+    ///
+    /// NOTE: Editors not known to have their own window are
+    /// assumed to run in the terminal, like `vi` and `nano`.
+    pub fn runs_in_terminal(&self) -> bool {
+        !matches!(
+            self.name(),
+            "code" | "code-insiders" | "codium" | "cursor" | "subl" | "zed" | "gvim" | "mate"
+        )
+    }
+
+    /// Create the command opening a location in the editor,
+    /// using the arguments each editor expects for it.
+    pub fn command(&self, location: &Location) -> Command {
+        let path = location.path.display();
+        let column = location.column.unwrap_or(1);
+        let location_args = match self.name() {
+            "code" | "code-insiders" | "codium" | "cursor" => vec![
+                String::from("--goto"),
+                format!("{path}:{}:{column}", location.line),
+            ],
+            "subl" | "zed" | "hx" | "helix" => vec![format!("{path}:{}:{column}", location.line)],
+            _ => vec![format!("+{}", location.line), path.to_string()],
+        };
+
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).args(location_args);
+        if !self.runs_in_terminal() {
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+        }
+
+        command
+    }
+}
@@ -0,0 +1,34 @@
+use crate::error::Error;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use std::{io::Write, path::PathBuf};
+
+/// The directory exported selections are saved in.
+///
+/// NOTE: This lives under `target` so saving a selection
+/// never triggers a rebuild (it's in the ignore list).
+const EXPORT_DIR: &str = "target/unlimited-ammo";
+
+/// Copy text to the system clipboard through the terminal, using
+/// the OSC 52 escape sequence.
+///
+/// NOTE: This works over SSH and inside tmux (with `set-clipboard`
+/// enabled), but some terminals don't support it at all.
+pub fn copy_to_clipboard(text: &str) -> Result<(), Error> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+/// Save text to a new timestamped file, returning the path it was saved to.
+pub fn save_to_file(text: &str) -> Result<PathBuf, Error> {
+    std::fs::create_dir_all(EXPORT_DIR)?;
+
+    let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.3fZ");
+    let path = PathBuf::from(EXPORT_DIR).join(format!("selection-{timestamp}.log"));
+    std::fs::write(&path, format!("{text}\n"))?;
+
+    Ok(path)
+}
@@ -0,0 +1,166 @@
+use crate::interface::theme;
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Clear, Widget},
+};
+
+/// What happened to the input prompt after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    /// Still typing in a line.
+    Editing,
+
+    /// The user went back to the normal mode.
+    Left,
+
+    /// The bytes to forward to the stdin of the running program.
+    Send(Vec<u8>),
+}
+
+/// The input mode prompt, forwarding what the user types to the stdin
+/// of the running program, either a line at a time or key by key (raw).
+///
+/// NOTE: `Esc` always goes back to normal mode, so it can't be forwarded.
+#[derive(Debug, Clone, Default)]
+pub struct InputPrompt {
+    /// The line being typed in (when not raw).
+    line: String,
+
+    /// Is every key press forwarded as it's typed ?
+    raw: bool,
+
+    /// Does the running program read from a pseudo-terminal ?
+    pty: bool,
+}
+impl InputPrompt {
+    /// Start forwarding input, a line at a time or key by key (raw),
+    /// to a program reading from a pseudo-terminal or a pipe.
+    pub fn open(&mut self, raw: bool, pty: bool) {
+        self.line.clear();
+        self.raw = raw;
+        self.pty = pty;
+    }
+
+    /// Handle a key press while forwarding input.
+    pub fn handle_key(&mut self, key: KeyEvent) -> InputEvent {
+        if key.code == KeyCode::Esc {
+            return InputEvent::Left;
+        }
+        if self.raw {
+            return self
+                .encode_key(key)
+                .map_or(InputEvent::Editing, InputEvent::Send);
+        }
+
+        match key.code {
+            KeyCode::Enter => {
+                let mut line = std::mem::take(&mut self.line).into_bytes();
+                line.push(b'\n');
+                return InputEvent::Send(line);
+            }
+            KeyCode::Backspace => {
+                self.line.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.line.clear();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.line.push(c);
+            }
+            _ => {}
+        }
+
+        InputEvent::Editing
+    }
+
+    /// Get the bytes a terminal would send for a key press.
+    ///
+    /// NOTE: Terminals send a carriage return for `Enter`, which a
+    /// pseudo-terminal in raw mode passes through untranslated.
+    fn encode_key(&self, key: KeyEvent) -> Option<Vec<u8>> {
+        let bytes = match key.code {
+            // Control characters (ex: `Ctrl-c` is ETX, `Ctrl-d` is EOT)
+            KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                vec![(c.to_ascii_lowercase() as u8) & 0x1f]
+            }
+            KeyCode::Char(c) => c.to_string().into_bytes(),
+            KeyCode::Enter if self.pty => vec![b'\r'],
+            KeyCode::Enter => vec![b'\n'],
+            KeyCode::Tab => vec![b'\t'],
+            KeyCode::Backspace => vec![0x7f],
+            KeyCode::Up => b"\x1b[A".to_vec(),
+            KeyCode::Down => b"\x1b[B".to_vec(),
+            KeyCode::Right => b"\x1b[C".to_vec(),
+            KeyCode::Left => b"\x1b[D".to_vec(),
+            KeyCode::Home => b"\x1b[H".to_vec(),
+            KeyCode::End => b"\x1b[F".to_vec(),
+            KeyCode::Delete => b"\x1b[3~".to_vec(),
+            _ => return None,
+        };
+
+        Some(bytes)
+    }
+
+    /// Render the prompt, showing what's being typed in.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        let (label, text) = match self.raw {
+            true => (
+                " stdin (raw) ",
+                String::from(" forwarding every key, Esc to leave"),
+            ),
+            false => (" stdin ", format!(" {}▏", self.line)),
+        };
+
+        Clear.render(area, buf);
+        Line::from(vec![
+            Span::styled(label, theme().key_binding.key),
+            Span::raw(text),
+        ])
+        .style(theme().root)
+        .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn raw_enter_is_a_carriage_return_in_a_pty() {
+        let mut input = InputPrompt::default();
+
+        input.open(true, true);
+        assert_eq!(
+            input.handle_key(press(KeyCode::Enter)),
+            InputEvent::Send(vec![b'\r'])
+        );
+
+        input.open(true, false);
+        assert_eq!(
+            input.handle_key(press(KeyCode::Enter)),
+            InputEvent::Send(vec![b'\n'])
+        );
+    }
+
+    #[test]
+    fn lines_end_with_a_newline() {
+        let mut input = InputPrompt::default();
+        input.open(false, true);
+
+        assert_eq!(
+            input.handle_key(press(KeyCode::Char('y'))),
+            InputEvent::Editing
+        );
+        assert_eq!(
+            input.handle_key(press(KeyCode::Enter)),
+            InputEvent::Send(b"y\n".to_vec())
+        );
+    }
+}
@@ -0,0 +1,620 @@
+use crate::{config::KeysConfig, error::Error};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the user can do in the interface by pressing a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    ScrollUp,
+    ScrollDown,
+    HalfPageUp,
+    HalfPageDown,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    ToggleFollow,
+    NextTab,
+    PrevTab,
+    SelectTab(usize),
+    CycleLayout,
+    FocusNextPane,
+    GrowPane,
+    ShrinkPane,
+    ToggleSelection,
+    CancelSelection,
+    CopySelection,
+    SaveSelection,
+    ToggleExportAnsi,
+    ToggleMouse,
+    ForwardInput,
+    ForwardRawInput,
+    RerunFailedTests,
+    CycleMode,
+    OpenBuildMenu,
+    PickBuildOption,
+    ApplyBuildOptions,
+    DiscardBuildOptions,
+    ShowHelp,
+    OpenPalette,
+}
+impl Action {
+    /// Every action, in the order they're listed to the user.
+    pub const ALL: [Action; 39] = [
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::HalfPageUp,
+        Action::HalfPageDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::ToggleFollow,
+        Action::NextTab,
+        Action::PrevTab,
+        Action::SelectTab(0),
+        Action::SelectTab(1),
+        Action::SelectTab(2),
+        Action::SelectTab(3),
+        Action::SelectTab(4),
+        Action::SelectTab(5),
+        Action::SelectTab(6),
+        Action::CycleLayout,
+        Action::FocusNextPane,
+        Action::GrowPane,
+        Action::ShrinkPane,
+        Action::ToggleSelection,
+        Action::CancelSelection,
+        Action::CopySelection,
+        Action::SaveSelection,
+        Action::ToggleExportAnsi,
+        Action::ToggleMouse,
+        Action::ForwardInput,
+        Action::ForwardRawInput,
+        Action::RerunFailedTests,
+        Action::CycleMode,
+        Action::OpenBuildMenu,
+        Action::PickBuildOption,
+        Action::ApplyBuildOptions,
+        Action::DiscardBuildOptions,
+        Action::ShowHelp,
+        Action::OpenPalette,
+        Action::Quit,
+    ];
+
+    /// Get the name of the action, as used in the config.
+    pub fn name(&self) -> String {
+        let name = match self {
+            Action::Quit => "quit",
+            Action::ScrollUp => "scroll_up",
+            Action::ScrollDown => "scroll_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::ToggleFollow => "toggle_follow",
+            Action::NextTab => "next_tab",
+            Action::PrevTab => "prev_tab",
+            Action::SelectTab(idx) => return format!("tab_{}", idx + 1),
+            Action::CycleLayout => "cycle_layout",
+            Action::FocusNextPane => "focus_next_pane",
+            Action::GrowPane => "grow_pane",
+            Action::ShrinkPane => "shrink_pane",
+            Action::ToggleSelection => "toggle_selection",
+            Action::CancelSelection => "cancel_selection",
+            Action::CopySelection => "copy_selection",
+            Action::SaveSelection => "save_selection",
+            Action::ToggleExportAnsi => "toggle_export_ansi",
+            Action::ToggleMouse => "toggle_mouse",
+            Action::ForwardInput => "forward_input",
+            Action::ForwardRawInput => "forward_raw_input",
+            Action::RerunFailedTests => "rerun_failed_tests",
+            Action::CycleMode => "cycle_mode",
+            Action::OpenBuildMenu => "open_build_menu",
+            Action::PickBuildOption => "pick_build_option",
+            Action::ApplyBuildOptions => "apply_build_options",
+            Action::DiscardBuildOptions => "discard_build_options",
+            Action::ShowHelp => "show_help",
+            Action::OpenPalette => "open_palette",
+        };
+
+        name.to_string()
+    }
+
+    /// Get a description of what the action does.
+    pub fn description(&self) -> String {
+        let description = match self {
+            Action::Quit => "Quit",
+            Action::ScrollUp => "Scroll up",
+            Action::ScrollDown => "Scroll down",
+            Action::HalfPageUp => "Scroll up half a page",
+            Action::HalfPageDown => "Scroll down half a page",
+            Action::PageUp => "Scroll up a page",
+            Action::PageDown => "Scroll down a page",
+            Action::Top => "Go to the first log",
+            Action::Bottom => "Go to the latest log (follow)",
+            Action::ToggleFollow => "Toggle following the latest log",
+            Action::NextTab => "Next tab",
+            Action::PrevTab => "Previous tab",
+            Action::SelectTab(idx) => return format!("Go to tab {}", idx + 1),
+            Action::CycleLayout => "Cycle the pane layout",
+            Action::FocusNextPane => "Focus the other pane",
+            Action::GrowPane => "Grow the focused pane",
+            Action::ShrinkPane => "Shrink the focused pane",
+            Action::ToggleSelection => "Start/stop selecting rows",
+            Action::CancelSelection => "Cancel the selection",
+            Action::CopySelection => "Copy the selection to the clipboard",
+            Action::SaveSelection => "Save the selection to a file",
+            Action::ToggleExportAnsi => "Toggle stripping colors from the selection",
+            Action::ToggleMouse => "Toggle mouse capture (off for the terminal's selection)",
+            Action::ForwardInput => "Type lines into the running app's stdin",
+            Action::ForwardRawInput => "Forward every key to the running app's stdin",
+            Action::RerunFailedTests => "Run the tests which failed again",
+            Action::CycleMode => "Switch to the next mode (run, test, check, clippy)",
+            Action::OpenBuildMenu => "Pick the profile, features and target of the builds",
+            Action::PickBuildOption => "Pick the build option under the cursor (build menu)",
+            Action::ApplyBuildOptions => "Apply the build options to the next build (build menu)",
+            Action::DiscardBuildOptions => "Discard the build options picked (build menu)",
+            Action::ShowHelp => "Show this help screen",
+            Action::OpenPalette => "Enter a command",
+        };
+
+        description.to_string()
+    }
+
+    /// Get an action by its name in the config.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// A key press (with modifiers) bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+impl Key {
+    /// Create a binding for a key press from a terminal event.
+    ///
+    /// NOTE: Shift is dropped for characters (`G` is already
+    /// shifted) and back tab, so they match their binding
+    /// regardless of how the terminal reports them.
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+
+    /// Parse a key binding from the config, like `q`, `G`,
+    /// `ctrl-d`, `alt-v`, `pagedown`, `shift-tab` or `esc`.
+    pub fn parse(binding: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidConfig(format!("invalid key binding `{binding}`"));
+
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = binding;
+        loop {
+            let lowercase_key = key.to_lowercase();
+            let (modifier, prefix_len) = if lowercase_key.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, "ctrl-".len())
+            } else if lowercase_key.starts_with("alt-") {
+                (KeyModifiers::ALT, "alt-".len())
+            } else if lowercase_key.starts_with("shift-") {
+                (KeyModifiers::SHIFT, "shift-".len())
+            } else {
+                break;
+            };
+
+            // A lone `-` after a modifier is the minus key itself
+            if key.len() == prefix_len {
+                break;
+            }
+            modifiers |= modifier;
+            key = &key[prefix_len..];
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            function_key if function_key.len() > 1 && function_key.starts_with('f') => {
+                KeyCode::F(function_key[1..].parse().map_err(|_| invalid())?)
+            }
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+
+        Ok(Self { code, modifiers })
+    }
+
+    /// Get a short label of the key binding to show the user (ex: `Ctrl-d`).
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => String::from("Space"),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Esc => String::from("Esc"),
+            KeyCode::Enter => String::from("Enter"),
+            KeyCode::Tab => String::from("Tab"),
+            KeyCode::BackTab => String::from("S-Tab"),
+            KeyCode::Backspace => String::from("Bksp"),
+            KeyCode::Delete => String::from("Del"),
+            KeyCode::Up => String::from("↑"),
+            KeyCode::Down => String::from("↓"),
+            KeyCode::Left => String::from("←"),
+            KeyCode::Right => String::from("→"),
+            KeyCode::PageUp => String::from("PgUp"),
+            KeyCode::PageDown => String::from("PgDn"),
+            KeyCode::Home => String::from("Home"),
+            KeyCode::End => String::from("End"),
+            KeyCode::F(n) => format!("F{n}"),
+            code => format!("{code:?}"),
+        };
+
+        let mut label = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            label.push_str("Ctrl-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            label.push_str("Alt-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            label.push_str("Shift-");
+        }
+        label.push_str(&key);
+
+        label
+    }
+}
+
+/// The key bindings of each action in the interface.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    /// The actions and the keys bound to them, in the order of `Action::ALL`.
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+impl Keymap {
+    /// The vim style keymap (default).
+    pub fn vim() -> Self {
+        Self::from_preset(&[
+            (Action::ScrollUp, &["k", "up"]),
+            (Action::ScrollDown, &["j", "down"]),
+            (Action::HalfPageUp, &["ctrl-u"]),
+            (Action::HalfPageDown, &["ctrl-d"]),
+            (Action::PageUp, &["pageup", "ctrl-b"]),
+            (Action::PageDown, &["pagedown", "ctrl-f"]),
+            (Action::Top, &["g", "home"]),
+            (Action::Bottom, &["G", "end"]),
+            (Action::ToggleFollow, &["f"]),
+            (Action::NextTab, &["tab"]),
+            (Action::PrevTab, &["shift-tab"]),
+            (Action::CycleLayout, &["l"]),
+            (Action::FocusNextPane, &["w"]),
+            (Action::GrowPane, &[">"]),
+            (Action::ShrinkPane, &["<"]),
+            (Action::ToggleSelection, &["v"]),
+            (Action::CancelSelection, &["esc"]),
+            (Action::CopySelection, &["y"]),
+            (Action::SaveSelection, &["s"]),
+            (Action::ToggleExportAnsi, &["a"]),
+            (Action::ToggleMouse, &["m"]),
+            (Action::ForwardInput, &["i"]),
+            (Action::ForwardRawInput, &["I"]),
+            (Action::RerunFailedTests, &["t"]),
+            (Action::CycleMode, &["M"]),
+            (Action::OpenBuildMenu, &["b"]),
+            (Action::PickBuildOption, &["space", "enter"]),
+            (Action::ApplyBuildOptions, &["esc"]),
+            (Action::DiscardBuildOptions, &["q"]),
+            (Action::ShowHelp, &["?"]),
+            (Action::OpenPalette, &[":"]),
+            (Action::Quit, &["q", "esc"]),
+        ])
+    }
+
+    /// The emacs style keymap.
+    pub fn emacs() -> Self {
+        Self::from_preset(&[
+            (Action::ScrollUp, &["ctrl-p", "up"]),
+            (Action::ScrollDown, &["ctrl-n", "down"]),
+            (Action::HalfPageUp, &["alt-p"]),
+            (Action::HalfPageDown, &["alt-n"]),
+            (Action::PageUp, &["alt-v", "pageup"]),
+            (Action::PageDown, &["ctrl-v", "pagedown"]),
+            (Action::Top, &["alt-<", "home"]),
+            (Action::Bottom, &["alt->", "end"]),
+            (Action::ToggleFollow, &["ctrl-s"]),
+            (Action::NextTab, &["tab"]),
+            (Action::PrevTab, &["shift-tab"]),
+            (Action::CycleLayout, &["ctrl-l"]),
+            (Action::FocusNextPane, &["ctrl-o"]),
+            (Action::GrowPane, &["alt-}"]),
+            (Action::ShrinkPane, &["alt-{"]),
+            (Action::ToggleSelection, &["ctrl-space"]),
+            (Action::CancelSelection, &["ctrl-g", "esc"]),
+            (Action::CopySelection, &["alt-w"]),
+            (Action::SaveSelection, &["ctrl-w"]),
+            (Action::ToggleExportAnsi, &["alt-a"]),
+            (Action::ToggleMouse, &["alt-m"]),
+            (Action::ForwardInput, &["alt-i"]),
+            (Action::ForwardRawInput, &["alt-I"]),
+            (Action::RerunFailedTests, &["alt-t"]),
+            (Action::CycleMode, &["alt-M"]),
+            (Action::OpenBuildMenu, &["alt-b"]),
+            (Action::PickBuildOption, &["space", "enter"]),
+            (Action::ApplyBuildOptions, &["esc"]),
+            (Action::DiscardBuildOptions, &["ctrl-g"]),
+            (Action::ShowHelp, &["f1", "?"]),
+            (Action::OpenPalette, &["alt-x"]),
+            (Action::Quit, &["ctrl-c", "ctrl-q"]),
+        ])
+    }
+
+    /// The arrow keys keymap, navigating without any letter keys.
+    pub fn arrows() -> Self {
+        Self::from_preset(&[
+            (Action::ScrollUp, &["up"]),
+            (Action::ScrollDown, &["down"]),
+            (Action::HalfPageUp, &["shift-up"]),
+            (Action::HalfPageDown, &["shift-down"]),
+            (Action::PageUp, &["pageup"]),
+            (Action::PageDown, &["pagedown"]),
+            (Action::Top, &["home"]),
+            (Action::Bottom, &["end"]),
+            (Action::ToggleFollow, &["f"]),
+            (Action::NextTab, &["right", "tab"]),
+            (Action::PrevTab, &["left", "shift-tab"]),
+            (Action::CycleLayout, &["l"]),
+            (Action::FocusNextPane, &["w"]),
+            (Action::GrowPane, &["shift-right"]),
+            (Action::ShrinkPane, &["shift-left"]),
+            (Action::ToggleSelection, &["space"]),
+            (Action::CancelSelection, &["esc"]),
+            (Action::CopySelection, &["y"]),
+            (Action::SaveSelection, &["s"]),
+            (Action::ToggleExportAnsi, &["a"]),
+            (Action::ToggleMouse, &["m"]),
+            (Action::ForwardInput, &["i"]),
+            (Action::ForwardRawInput, &["I"]),
+            (Action::RerunFailedTests, &["t"]),
+            (Action::CycleMode, &["M"]),
+            (Action::OpenBuildMenu, &["b"]),
+            (Action::PickBuildOption, &["space", "enter"]),
+            (Action::ApplyBuildOptions, &["esc"]),
+            (Action::DiscardBuildOptions, &["q"]),
+            (Action::ShowHelp, &["?", "f1"]),
+            (Action::OpenPalette, &[":"]),
+            (Action::Quit, &["q", "esc"]),
+        ])
+    }
+
+    /// Create a keymap from a list of actions and their key bindings.
+    ///
+    /// NOTE: The tabs are always bound to their number keys, and the
+    /// bindings of presets are known to be valid, so they're unwrapped.
+    fn from_preset(preset: &[(Action, &[&str])]) -> Self {
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = match action {
+                    Action::SelectTab(idx) => {
+                        let key = char::from_digit(idx as u32 + 1, 10).unwrap_or('0');
+                        vec![Key::parse(&key.to_string())]
+                    }
+                    action => preset
+                        .iter()
+                        .filter(|(preset_action, _)| *preset_action == action)
+                        .flat_map(|(_, keys)| keys.iter().map(|key| Key::parse(key)))
+                        .collect(),
+                };
+                let keys = keys.into_iter().filter_map(Result::ok).collect();
+
+                (action, keys)
+            })
+            .collect();
+
+        Self { bindings }
+    }
+
+    /// Create the keymap described in the config, a preset with
+    /// any key binding overrides applied on top of it.
+    pub fn from_config(config: &KeysConfig) -> Result<Self, Error> {
+        let mut keymap = match config.preset.as_deref() {
+            None | Some("vim") => Self::vim(),
+            Some("emacs") => Self::emacs(),
+            Some("arrows") => Self::arrows(),
+            Some(preset) => {
+                return Err(Error::InvalidConfig(format!(
+                    "unknown keys preset `{preset}`, expected vim, emacs or arrows"
+                )));
+            }
+        };
+
+        for (name, keys) in &config.bindings {
+            let action = Action::from_name(name)
+                .ok_or_else(|| Error::InvalidConfig(format!("unknown action `{name}`")))?;
+            let keys = keys
+                .iter()
+                .map(|key| Key::parse(key))
+                .collect::<Result<Vec<Key>, Error>>()?;
+
+            if let Some((_, bindings)) = keymap.bindings.iter_mut().find(|(a, _)| *a == action) {
+                *bindings = keys;
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// Get the actions bound to a key press, in order of priority.
+    pub fn actions(&self, event: &KeyEvent) -> Vec<Action> {
+        let key = Key::from_event(event);
+
+        self.bindings
+            .iter()
+            .filter(|(_, bindings)| bindings.contains(&key))
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Get the labels of the keys bound to an action, joined
+    /// together (ex: `k/↑`), `None` if it's not bound at all.
+    pub fn label(&self, action: Action) -> Option<String> {
+        let (_, bindings) = self.bindings.iter().find(|(a, _)| *a == action)?;
+        if bindings.is_empty() {
+            return None;
+        }
+
+        Some(
+            bindings
+                .iter()
+                .map(Key::label)
+                .collect::<Vec<String>>()
+                .join("/"),
+        )
+    }
+
+    /// Get a compact label of the first key bound to each of the actions,
+    /// joined together (ex: `k/j`), skipping the actions that aren't bound.
+    pub fn short_label(&self, actions: &[Action]) -> Option<String> {
+        let keys: Vec<String> = actions
+            .iter()
+            .filter_map(|action| {
+                let (_, bindings) = self.bindings.iter().find(|(a, _)| a == action)?;
+                bindings.first().map(Key::label)
+            })
+            .collect();
+
+        match keys.is_empty() {
+            true => None,
+            false => Some(keys.join("/")),
+        }
+    }
+
+    /// Get every action with a label of its key bindings, for the help screen.
+    pub fn help_entries(&self) -> Vec<(String, String)> {
+        Action::ALL
+            .into_iter()
+            .filter_map(|action| Some((self.label(action)?, action.description())))
+            .collect()
+    }
+}
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::vim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn keymap(config: &str) -> Result<Keymap, Error> {
+        let config: Config = toml::from_str(config).unwrap();
+        Keymap::from_config(&config.keys)
+    }
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        let ctrl_c = Key::parse("ctrl-c").unwrap();
+        assert_eq!(ctrl_c.code, KeyCode::Char('c'));
+        assert_eq!(ctrl_c.modifiers, KeyModifiers::CONTROL);
+
+        let alt_minus = Key::parse("alt--").unwrap();
+        assert_eq!(alt_minus.code, KeyCode::Char('-'));
+        assert_eq!(alt_minus.modifiers, KeyModifiers::ALT);
+    }
+
+    #[test]
+    fn parses_uppercase_characters_without_shift() {
+        let key = Key::parse("F").unwrap();
+
+        assert_eq!(key.code, KeyCode::Char('F'));
+        assert_eq!(key.modifiers, KeyModifiers::NONE);
+        assert_eq!(Key::parse("f5").unwrap().code, KeyCode::F(5));
+    }
+
+    #[test]
+    fn parses_shift_tab_as_back_tab() {
+        let key = Key::parse("shift-tab").unwrap();
+
+        assert_eq!(key.code, KeyCode::BackTab);
+        assert_eq!(key.modifiers, KeyModifiers::NONE);
+        assert_eq!(
+            Key::from_event(&press(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            key
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        for binding in ["", "ctrl-", "fx", "qq", "ctrl-pageupp", "hyper-a"] {
+            assert!(Key::parse(binding).is_err(), "{binding:?} was parsed");
+        }
+    }
+
+    #[test]
+    fn bindings_override_the_preset() {
+        let keymap = keymap(
+            r#"
+            [keys]
+            preset = "emacs"
+
+            [keys.bindings]
+            toggle_follow = ["F"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            keymap.actions(&press(KeyCode::Char('F'), KeyModifiers::SHIFT)),
+            vec![Action::ToggleFollow]
+        );
+        assert_eq!(keymap.label(Action::ToggleFollow).as_deref(), Some("F"));
+        // The other bindings of the preset are kept
+        assert_eq!(
+            keymap.label(Action::OpenBuildMenu),
+            Keymap::emacs().label(Action::OpenBuildMenu)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_presets_and_actions() {
+        assert!(keymap("[keys]\npreset = \"nano\"").is_err());
+        assert!(keymap("[keys.bindings]\nfly = [\"f\"]").is_err());
+        assert!(keymap("[keys.bindings]\nquit = [\"ctrl-\"]").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::interface::Tab;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The smallest share (percentage) of the screen a pane can be resized to.
+const MIN_PANE_PERCENT: u16 = 20;
+
+/// How much (percentage) a pane grows or shrinks on each resize.
+const RESIZE_STEP_PERCENT: u16 = 5;
+
+/// How the panes of the interface are split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Split {
+    /// A single pane, showing the focused pane only.
+    #[default]
+    Single,
+
+    /// Two panes side by side.
+    Horizontal,
+
+    /// Two panes stacked on top of each other.
+    Vertical,
+}
+
+/// The layout of the panes in the interface, each pane showing a tab.
+///
+/// NOTE: This is remembered across sessions, see `load` and `save`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneLayout {
+    /// How the panes are split.
+    pub split: Split,
+
+    /// The share (percentage) of the screen taken by the first pane.
+    pub first_pane_percent: u16,
+
+    /// The tab shown in each pane.
+    pub panes: [Tab; 2],
+
+    /// The index of the pane with focus (receives key presses).
+    pub focused: usize,
+}
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            split: Split::Single,
+            first_pane_percent: 50,
+            panes: [Tab::All, Tab::App],
+            focused: 0,
+        }
+    }
+}
+impl PaneLayout {
+    /// Cycle through the ways of splitting the panes.
+    pub fn cycle_split(&mut self) {
+        self.split = match self.split {
+            Split::Single => Split::Horizontal,
+            Split::Horizontal => Split::Vertical,
+            Split::Vertical => Split::Single,
+        };
+    }
+
+    /// Move focus to the other pane.
+    pub fn focus_next_pane(&mut self) {
+        self.focused = (self.focused + 1) % self.panes.len();
+    }
+
+    /// Grow the focused pane, shrinking the other one.
+    pub fn grow_focused_pane(&mut self) {
+        match self.focused {
+            0 => self.resize_first_pane(RESIZE_STEP_PERCENT as i16),
+            _ => self.resize_first_pane(-(RESIZE_STEP_PERCENT as i16)),
+        }
+    }
+
+    /// Shrink the focused pane, growing the other one.
+    pub fn shrink_focused_pane(&mut self) {
+        match self.focused {
+            0 => self.resize_first_pane(-(RESIZE_STEP_PERCENT as i16)),
+            _ => self.resize_first_pane(RESIZE_STEP_PERCENT as i16),
+        }
+    }
+
+    /// Resize the first pane by a (positive or negative) percentage.
+    fn resize_first_pane(&mut self, delta: i16) {
+        let percent = self.first_pane_percent as i16 + delta;
+        self.first_pane_percent =
+            percent.clamp(MIN_PANE_PERCENT as i16, 100 - MIN_PANE_PERCENT as i16) as u16;
+    }
+
+    /// Get the indexes of the panes currently visible.
+    pub fn visible_panes(&self) -> Vec<usize> {
+        match self.split {
+            Split::Single => vec![self.focused],
+            Split::Horizontal | Split::Vertical => (0..self.panes.len()).collect(),
+        }
+    }
+
+    /// Load the layout remembered from the last session,
+    /// falling back to the default layout.
+    ///
+    /// NOTE: The file could have been edited by hand, so a layout
+    /// out of bounds is dropped too (rather than crash the interface).
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(Self::is_valid)
+            .unwrap_or_default()
+    }
+
+    /// Is the layout within bounds, the focused pane existing and the
+    /// first pane within the range it can be resized to ?
+    fn is_valid(&self) -> bool {
+        self.focused < self.panes.len()
+            && (MIN_PANE_PERCENT..=100 - MIN_PANE_PERCENT).contains(&self.first_pane_percent)
+    }
+
+    /// Remember the layout for the next session.
+    ///
+    /// NOTE: Failing to save isn't worth interrupting the user
+    /// over, the layout just won't be remembered.
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(self) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// Get the path the layout is remembered in, following the XDG base
+    /// directory spec (ex: `~/.local/state/unlimited-ammo/layout.json`).
+    fn path() -> Option<PathBuf> {
+        let state_dir = match std::env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/state"),
+        };
+
+        Some(state_dir.join("unlimited-ammo").join("layout.json"))
+    }
+}
@@ -1,8 +1,30 @@
 mod app;
 pub use app::App;
 
+mod build_menu;
+pub use build_menu::BuildMenu;
+
 mod display;
-pub use display::Display;
+pub use display::{Display, Filter, LogEntry};
+
+mod editor;
+mod export;
+mod input;
+
+pub mod keymap;
+pub use keymap::{Action, Keymap};
+
+mod layout;
+pub use layout::{PaneLayout, Split};
+
+mod palette;
+pub use palette::{Palette, PaletteCommand};
+
+mod status;
+pub use status::{ProcessStatus, ProcessStatuses, TestStatus};
+
+mod tabs;
+pub use tabs::{Tab, Tabs};
 
-mod theme;
-pub use theme::THEME;
+pub mod theme;
+pub use theme::{Theme, theme};
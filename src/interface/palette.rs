@@ -0,0 +1,349 @@
+use crate::{
+    error::Error,
+    event::LogSource,
+    interface::{Filter, theme},
+    mode::Mode,
+};
+use ratatui::{
+    buffer::Buffer,
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Clear, Widget},
+};
+
+/// A command entered in the command palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    /// Rebuild (and restart) the codebase.
+    Rebuild,
+
+    /// Run a shell command (ex: `cargo test`).
+    Exec(String),
+
+    /// Run the tests, only the ones which failed the last time if `only_failed`.
+    Test { only_failed: bool },
+
+    /// Switch the watcher to another mode (ex: `clippy`).
+    SetMode(Mode),
+
+    /// Open the menu picking the profile, features and target of the builds.
+    Build,
+
+    /// Change how fast (in seconds) the watcher checks files for updates.
+    SetInterval(u8),
+
+    /// Clear the log messages of the current tab.
+    Clear,
+
+    /// Only show the log messages matching a filter in the current tab,
+    /// or all of them again when `None`.
+    Filter(Option<Filter>),
+
+    /// Show the help screen.
+    Help,
+
+    /// Quit the application.
+    Quit,
+}
+impl PaletteCommand {
+    /// The name, arguments, and description of each command.
+    pub const ALL: [(&'static str, &'static str, &'static str); 10] = [
+        ("rebuild", "", "Rebuild and restart the app"),
+        ("exec", "<command>", "Run a shell command (ex: cargo test)"),
+        ("test", "[failed]", "Run the tests, or only the failed ones"),
+        ("mode", "<mode>", "Switch mode: run, test, check or clippy"),
+        ("build", "", "Pick the profile, features and target"),
+        (
+            "set",
+            "interval <seconds>",
+            "Change how often files are checked",
+        ),
+        ("clear", "", "Clear the log messages of the current tab"),
+        (
+            "filter",
+            "[source|text]",
+            "Only show matching log messages, all again without one",
+        ),
+        ("help", "", "Show the help screen"),
+        ("quit", "", "Quit"),
+    ];
+
+    /// Parse a command entered in the command palette (ex: `set interval 1`).
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let input = input.trim();
+        let (name, args) = input.split_once(' ').unwrap_or((input, ""));
+        let args = args.trim();
+
+        let command = match name {
+            "rebuild" | "r" => Self::Rebuild,
+            "exec" if !args.is_empty() => Self::Exec(args.to_string()),
+            "exec" => {
+                return Err(Error::InvalidCommand(String::from(
+                    "`exec` needs a command to run (ex: `exec cargo test`)",
+                )));
+            }
+            "test" | "t" => match args {
+                "" => Self::Test { only_failed: false },
+                "failed" => Self::Test { only_failed: true },
+                _ => {
+                    return Err(Error::InvalidCommand(String::from(
+                        "expected `test` or `test failed`",
+                    )));
+                }
+            },
+            "build" | "b" => Self::Build,
+            "mode" => match Mode::from_name(args) {
+                Some(mode) => Self::SetMode(mode),
+                None => {
+                    return Err(Error::InvalidCommand(String::from(
+                        "expected `mode run`, `mode test`, `mode check` or `mode clippy`",
+                    )));
+                }
+            },
+            "set" => match args.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["interval", seconds] => match seconds.parse::<u8>() {
+                    Ok(seconds) if seconds > 0 => Self::SetInterval(seconds),
+                    _ => {
+                        return Err(Error::InvalidCommand(format!(
+                            "invalid interval `{seconds}`, expected 1 to 255 seconds"
+                        )));
+                    }
+                },
+                _ => {
+                    return Err(Error::InvalidCommand(String::from(
+                        "expected `set interval <seconds>`",
+                    )));
+                }
+            },
+            "clear" => Self::Clear,
+            "filter" if args.is_empty() => Self::Filter(None),
+            "filter" => Self::Filter(Some(Filter::parse(args))),
+            "help" | "h" => Self::Help,
+            "quit" | "q" => Self::Quit,
+            name => {
+                return Err(Error::InvalidCommand(format!("unknown command `{name}`")));
+            }
+        };
+
+        Ok(command)
+    }
+}
+
+/// What happened to the command palette after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteInput {
+    /// Still entering a command.
+    Editing,
+
+    /// The user backed out of the command palette.
+    Cancelled,
+
+    /// The user entered a command.
+    Submitted(String),
+}
+
+/// The command palette (`:`), where commands are typed in with
+/// completion, and previously entered commands can be recalled.
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    /// The command being entered.
+    input: String,
+
+    /// The previously entered commands, oldest first.
+    history: Vec<String>,
+
+    /// The command in `history` being recalled, if any.
+    history_idx: Option<usize>,
+}
+impl Palette {
+    /// The max number of commands remembered in the history.
+    const MAX_HISTORY: usize = 100;
+
+    /// Start entering a new command.
+    pub fn open(&mut self) {
+        self.input.clear();
+        self.history_idx = None;
+    }
+
+    /// Handle a key press while entering a command.
+    pub fn handle_key(&mut self, key: KeyEvent) -> PaletteInput {
+        match key.code {
+            KeyCode::Esc => return PaletteInput::Cancelled,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return PaletteInput::Cancelled;
+            }
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Backspace if self.input.is_empty() => return PaletteInput::Cancelled,
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.clear();
+            }
+            KeyCode::Tab => self.complete(),
+            KeyCode::Up => self.recall_prev(),
+            KeyCode::Down => self.recall_next(),
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.input.push(c)
+            }
+            _ => {}
+        }
+
+        PaletteInput::Editing
+    }
+
+    /// Enter the current command, remembering it in the history.
+    fn submit(&mut self) -> PaletteInput {
+        let input = self.input.trim().to_string();
+        if input.is_empty() {
+            return PaletteInput::Cancelled;
+        }
+
+        if self.history.last() != Some(&input) {
+            self.history.push(input.clone());
+        }
+        if self.history.len() > Self::MAX_HISTORY {
+            self.history.remove(0);
+        }
+
+        PaletteInput::Submitted(input)
+    }
+
+    /// Recall the previous (older) command in the history.
+    fn recall_prev(&mut self) {
+        let idx = match self.history_idx {
+            Some(idx) => idx.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+
+        self.history_idx = Some(idx);
+        self.input = self.history[idx].clone();
+    }
+
+    /// Recall the next (newer) command in the history, or
+    /// an empty command after the most recent one.
+    fn recall_next(&mut self) {
+        let Some(idx) = self.history_idx else {
+            return;
+        };
+
+        if idx + 1 < self.history.len() {
+            self.history_idx = Some(idx + 1);
+            self.input = self.history[idx + 1].clone();
+        } else {
+            self.history_idx = None;
+            self.input.clear();
+        }
+    }
+
+    /// Get the possible completions of the command being entered.
+    pub fn completions(&self) -> Vec<String> {
+        let (name, args) = match self.input.split_once(' ') {
+            Some((name, args)) => (name, Some(args)),
+            None => (self.input.as_str(), None),
+        };
+
+        let candidates: Vec<String> = match (name, args) {
+            (name, None) => PaletteCommand::ALL
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .filter(|command| command.starts_with(name))
+                .collect(),
+            ("set", Some(args)) => Vec::from([String::from("set interval ")])
+                .into_iter()
+                .filter(|command| command["set ".len()..].starts_with(args))
+                .collect(),
+            ("filter", Some(args)) => LogSource::ALL
+                .iter()
+                .filter(|source| source.name().starts_with(args))
+                .map(|source| format!("filter {}", source.name()))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        candidates
+            .into_iter()
+            .filter(|candidate| *candidate != self.input)
+            .collect()
+    }
+
+    /// Complete the command being entered, as far as all the
+    /// possible completions agree with each other.
+    fn complete(&mut self) {
+        let completions = self.completions();
+        let Some(first) = completions.first() else {
+            return;
+        };
+
+        let common_len = completions.iter().fold(first.len(), |len, completion| {
+            first
+                .chars()
+                .zip(completion.chars())
+                .take_while(|(a, b)| a == b)
+                .count()
+                .min(len)
+        });
+        let mut completed: String = first.chars().take(common_len).collect();
+
+        // Move on to the arguments of a fully completed command
+        let takes_args = PaletteCommand::ALL
+            .iter()
+            .any(|(name, args, _)| *name == completed && !args.is_empty());
+        if completions.len() == 1 && takes_args {
+            completed.push(' ');
+        }
+
+        if completed.len() >= self.input.len() {
+            self.input = completed;
+        }
+    }
+
+    /// Render the command being entered, with the possible
+    /// completions listed in a popup above it.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        Line::from(vec![
+            Span::styled(":", theme().key_binding.key),
+            Span::raw(format!(" {}▏", self.input)),
+        ])
+        .style(theme().root)
+        .render(area, buf);
+
+        let completions = self.completions();
+        if completions.is_empty() || self.input.is_empty() {
+            return;
+        }
+
+        let height = (completions.len() as u16).min(area.y);
+        let width = completions
+            .iter()
+            .map(|completion| completion.chars().count() as u16 + 4)
+            .max()
+            .unwrap_or_default()
+            .min(area.width);
+        let popup_area = Rect {
+            x: area.x,
+            y: area.y.saturating_sub(height),
+            width,
+            height,
+        };
+
+        Clear.render(popup_area, buf);
+        for (idx, completion) in completions.iter().take(height as usize).enumerate() {
+            let row_area = Rect {
+                y: popup_area.y + idx as u16,
+                height: 1,
+                ..popup_area
+            };
+            Line::from(format!("  {completion}"))
+                .style(theme().highlight)
+                .render(row_area, buf);
+        }
+    }
+}
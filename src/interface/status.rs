@@ -0,0 +1,241 @@
+use crate::{
+    event::{BuildKind, Event},
+    interface::theme,
+    mode::Mode,
+    process::APP,
+};
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+/// The status of a supervised process (the app, or one declared in the config).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProcessStatus {
+    /// The process wasn't started yet (ex: the app during the initial build).
+    #[default]
+    NotStarted,
+
+    /// The process is running, but not ready yet (see `ReadinessProbe`).
+    Starting,
+
+    /// The process is ready, `duration_ms` after the change (or restart) leading to it.
+    Ready { duration_ms: u128 },
+
+    /// The process exited, `code` is `None` when it was killed.
+    Exited { code: Option<i32> },
+
+    /// The process exited, and is waiting to be restarted.
+    Restarting { attempt: u32, max_attempts: u32 },
+
+    /// The process kept exiting, so it won't be restarted until the next change (or build).
+    CrashLoop { restarts: u32 },
+}
+impl ProcessStatus {
+    /// Update the status following an event published by the `Watcher`.
+    pub fn update(&mut self, event: &Event) {
+        *self = match event {
+            Event::ProcessStarted { .. } => ProcessStatus::Starting,
+            Event::Ready { duration_ms, .. } => ProcessStatus::Ready {
+                duration_ms: *duration_ms,
+            },
+            Event::ProcessExited { code, .. } => ProcessStatus::Exited { code: *code },
+            Event::RestartScheduled {
+                attempt,
+                max_attempts,
+                ..
+            } => ProcessStatus::Restarting {
+                attempt: *attempt,
+                max_attempts: *max_attempts,
+            },
+            Event::CrashLoop { restarts, .. } => ProcessStatus::CrashLoop {
+                restarts: *restarts,
+            },
+            _ => return,
+        };
+    }
+
+    /// Get the status as a styled span, prefixed with the name of
+    /// the process (if any), if there's anything to show.
+    pub fn span(&self, name: Option<&str>) -> Option<Span<'static>> {
+        let (text, style): (String, Style) = match self {
+            ProcessStatus::NotStarted => return None,
+            ProcessStatus::Starting => (String::from("◌ starting"), theme().status_warning),
+            ProcessStatus::Ready { duration_ms } => (
+                format!("● ready in {:.2}s", *duration_ms as f64 / 1000.0),
+                theme().status,
+            ),
+            ProcessStatus::Exited { code: Some(0) } => {
+                (String::from("● exited"), theme().status_warning)
+            }
+            ProcessStatus::Exited { code: Some(code) } => {
+                (format!("● exited with code {code}"), theme().status_error)
+            }
+            ProcessStatus::Exited { code: None } => {
+                (String::from("● killed"), theme().status_error)
+            }
+            ProcessStatus::Restarting {
+                attempt,
+                max_attempts,
+            } => (
+                format!("⟳ restarting ({attempt}/{max_attempts})"),
+                theme().status_warning,
+            ),
+            ProcessStatus::CrashLoop { restarts } => (
+                format!("✖ crash loop, gave up after {restarts} restarts"),
+                theme().status_error,
+            ),
+        };
+
+        let text = match name {
+            Some(name) => format!(" {name} {text} "),
+            None => format!(" {text} "),
+        };
+
+        Some(Span::styled(text, style))
+    }
+}
+
+/// The status of every supervised process, shown in the title bar.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessStatuses {
+    /// The status of each process by name, the app first.
+    statuses: Vec<(String, ProcessStatus)>,
+}
+impl ProcessStatuses {
+    /// Update the status of a process following an event published by the `Watcher`.
+    pub fn update(&mut self, event: &Event) {
+        // NOTE: The app isn't ran outside of the run mode, so it has no status.
+        if let Event::ModeChanged { mode } = event
+            && *mode != Mode::Run
+        {
+            self.statuses.retain(|(name, _)| name != APP);
+            return;
+        }
+
+        let name = match event {
+            Event::ProcessStarted { name, .. }
+            | Event::Ready { name, .. }
+            | Event::ProcessExited { name, .. }
+            | Event::RestartScheduled { name, .. }
+            | Event::CrashLoop { name, .. } => name,
+            _ => return,
+        };
+
+        let idx = match self.statuses.iter().position(|(other, _)| other == name) {
+            Some(idx) => idx,
+            None => {
+                let idx = match name.as_str() {
+                    APP => 0,
+                    _ => self.statuses.len(),
+                };
+                self.statuses
+                    .insert(idx, (name.clone(), ProcessStatus::default()));
+                idx
+            }
+        };
+        self.statuses[idx].1.update(event);
+    }
+
+    /// Get the status of every process as a line, if there's anything to show.
+    ///
+    /// NOTE: The names of the processes are left out when the app is the only one.
+    pub fn line(&self) -> Option<Line<'static>> {
+        let show_names = self.statuses.iter().any(|(name, _)| name != APP);
+        let spans: Vec<Span<'static>> = self
+            .statuses
+            .iter()
+            .filter_map(|(name, status)| status.span(show_names.then_some(name.as_str())))
+            .collect();
+
+        (!spans.is_empty()).then(|| Line::from(spans))
+    }
+}
+
+/// The status of the tests (in test mode), shown in the title bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The tests weren't ran yet (ex: not in test mode).
+    #[default]
+    NotRun,
+
+    /// The tests are being built and ran.
+    Running,
+
+    /// The tests ran, with the counts of each outcome.
+    Finished {
+        passed: usize,
+        failed: usize,
+        ignored: usize,
+    },
+
+    /// The tests didn't compile, so none of them ran.
+    CompileError,
+}
+impl TestStatus {
+    /// Update the status following an event published by the `Watcher`.
+    pub fn update(&mut self, event: &Event) {
+        *self = match event {
+            Event::BuildStarted {
+                kind: BuildKind::Tests,
+            } => TestStatus::Running,
+            // NOTE: The last results are stale in another mode.
+            Event::ModeChanged { .. } => TestStatus::NotRun,
+            Event::TestsFinished {
+                passed,
+                failed,
+                ignored,
+                ..
+            } => TestStatus::Finished {
+                passed: *passed,
+                failed: *failed,
+                ignored: *ignored,
+            },
+            // NOTE: Without a single result, the test run failing means they didn't compile.
+            Event::BuildFinished {
+                kind: BuildKind::Tests,
+                success: false,
+                ..
+            } if *self
+                == (TestStatus::Finished {
+                    passed: 0,
+                    failed: 0,
+                    ignored: 0,
+                }) =>
+            {
+                TestStatus::CompileError
+            }
+            _ => return,
+        };
+    }
+
+    /// Get the status as a styled span, if there's anything to show.
+    pub fn span(&self) -> Option<Span<'static>> {
+        let (text, style) = match *self {
+            TestStatus::NotRun => return None,
+            TestStatus::Running => (String::from("◌ testing"), theme().status_warning),
+            TestStatus::Finished {
+                passed,
+                failed: 0,
+                ignored: 0,
+            } => (format!("✔ {passed} passed"), theme().status),
+            TestStatus::Finished {
+                passed,
+                failed: 0,
+                ignored,
+            } => (
+                format!("✔ {passed} passed, {ignored} ignored"),
+                theme().status,
+            ),
+            TestStatus::Finished { passed, failed, .. } => (
+                format!("✖ {failed} failed, {passed} passed"),
+                theme().status_error,
+            ),
+            TestStatus::CompileError => {
+                (String::from("✖ tests didn't compile"), theme().status_error)
+            }
+        };
+
+        Some(Span::styled(format!(" {text} "), style))
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    event::{BuildKind, Event, LogSource, TestOutcome},
+    interface::{
+        Display, PaneLayout, ProcessStatuses, TestStatus,
+        theme::{paint, theme},
+    },
+    mode::Mode,
+    sink::EventSink,
+    watcher::Watcher,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// The tabs within the interface, each one displaying
+/// a different kind of log messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tab {
+    /// Every log message, as they came in.
+    All,
+
+    /// The output of the compiler (cargo) building the codebase.
+    Build,
+
+    /// The output (stdout & stderr) of the running app.
+    App,
+
+    /// The output of the web build tool.
+    Web,
+
+    /// The diagnostics (errors, warnings) of the latest build.
+    Diagnostics,
+
+    /// The events of Unlimited Ammo itself (changes, builds, exits).
+    Events,
+
+    /// The results of the latest test run, the failed tests with their output.
+    Tests,
+}
+impl Tab {
+    /// Every tab, in the order they're displayed.
+    pub const ALL: [Tab; 7] = [
+        Tab::All,
+        Tab::Build,
+        Tab::App,
+        Tab::Web,
+        Tab::Diagnostics,
+        Tab::Events,
+        Tab::Tests,
+    ];
+
+    /// Get the title of the tab.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Tab::All => "All",
+            Tab::Build => "Build",
+            Tab::App => "App",
+            Tab::Web => "Web",
+            Tab::Diagnostics => "Diagnostics",
+            Tab::Events => "Events",
+            Tab::Tests => "Tests",
+        }
+    }
+
+    /// Get the position of the tab, in the order they're displayed.
+    pub fn index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|tab| tab == self)
+            .unwrap_or_default()
+    }
+
+    /// Get the tab the given log source belongs to.
+    fn from_log_source(source: LogSource) -> Self {
+        match source {
+            LogSource::Watcher => Tab::Events,
+            LogSource::Build => Tab::Build,
+            LogSource::Web => Tab::Web,
+            LogSource::Stdout | LogSource::Stderr => Tab::App,
+        }
+    }
+}
+
+/// The tabs of the interface, each with their own `Display`
+/// (log messages and scroll state), and the panes showing them.
+#[derive(Debug)]
+pub struct Tabs {
+    /// The display of each tab, in the same order as `Tab::ALL`.
+    displays: Vec<Display>,
+
+    /// Does the tab have log messages the user hasn't seen ?
+    /// (in the same order as `Tab::ALL`)
+    unread: Vec<bool>,
+
+    /// The layout of the panes, and the tab shown in each.
+    pub layout: PaneLayout,
+
+    /// The status of the app, and the other supervised processes.
+    pub statuses: ProcessStatuses,
+
+    /// The status of the tests, in test mode.
+    pub tests: TestStatus,
+
+    /// The mode the `Watcher` is in (ex: running the tests).
+    pub mode: Mode,
+}
+impl Tabs {
+    /// Create a new `Tabs` instance
+    pub fn new(layout: PaneLayout) -> Self {
+        Self {
+            displays: Tab::ALL.iter().map(|_| Display::new()).collect(),
+            unread: vec![false; Tab::ALL.len()],
+            layout,
+            statuses: ProcessStatuses::default(),
+            tests: TestStatus::default(),
+            mode: Mode::default(),
+        }
+    }
+
+    /// Get the tab selected in the focused pane.
+    pub fn selected(&self) -> Tab {
+        self.layout.panes[self.layout.focused]
+    }
+
+    /// Select a tab in the focused pane, marking its log messages as read.
+    pub fn select(&mut self, tab: Tab) {
+        self.layout.panes[self.layout.focused] = tab;
+        self.unread[tab.index()] = false;
+    }
+
+    /// Select the next tab, wrapping around to the first.
+    pub fn select_next(&mut self) {
+        let idx = (self.selected().index() + 1) % Tab::ALL.len();
+        self.select(Tab::ALL[idx]);
+    }
+
+    /// Select the previous tab, wrapping around to the last.
+    pub fn select_prev(&mut self) {
+        let idx = (self.selected().index() + Tab::ALL.len() - 1) % Tab::ALL.len();
+        self.select(Tab::ALL[idx]);
+    }
+
+    /// Move focus to the other pane, marking its log messages as read.
+    pub fn focus_next_pane(&mut self) {
+        self.layout.focus_next_pane();
+        self.select(self.selected());
+    }
+
+    /// Does the tab have log messages the user hasn't seen ?
+    pub fn is_unread(&self, tab: Tab) -> bool {
+        self.unread[tab.index()]
+    }
+
+    /// Is the tab shown in one of the visible panes ?
+    fn is_visible(&self, tab: Tab) -> bool {
+        self.layout
+            .visible_panes()
+            .into_iter()
+            .any(|pane| self.layout.panes[pane] == tab)
+    }
+
+    /// Get the display of the tab selected in the focused pane.
+    pub fn display(&mut self) -> &mut Display {
+        let idx = self.selected().index();
+        &mut self.displays[idx]
+    }
+
+    /// Get the display of the given tab.
+    pub fn display_of(&mut self, tab: Tab) -> &mut Display {
+        &mut self.displays[tab.index()]
+    }
+
+    /// Add a log message to a tab, marking it as unread
+    /// if it's not shown in one of the visible panes.
+    pub fn add_log(&mut self, tab: Tab, source: LogSource, log: String) {
+        self.displays[tab.index()].add_log(source, log);
+        if !self.is_visible(tab) {
+            self.unread[tab.index()] = true;
+        }
+    }
+
+    /// Clear all the log messages of a tab, keeping its filter.
+    pub fn clear(&mut self, tab: Tab) {
+        let filter = self.displays[tab.index()].filter.take();
+        self.displays[tab.index()] = Display::new();
+        self.displays[tab.index()].filter = filter;
+        self.unread[tab.index()] = false;
+    }
+
+    /// Format a diagnostic to display in the diagnostics tab,
+    /// preferring how the compiler rendered it.
+    fn format_diagnostic(diagnostic: &crate::event::Diagnostic) -> String {
+        if let Some(rendered) = &diagnostic.rendered {
+            return rendered.trim_end().to_string();
+        }
+
+        match (&diagnostic.file, diagnostic.line, diagnostic.column) {
+            (Some(file), Some(line), Some(column)) => format!(
+                "{}: {}\n --> {file}:{line}:{column}",
+                diagnostic.level, diagnostic.message
+            ),
+            _ => format!("{}: {}", diagnostic.level, diagnostic.message),
+        }
+    }
+
+    /// Format the results of a test run to display in the tests tab, a
+    /// summary followed by every failed test with its captured output.
+    fn format_test_results(
+        passed: usize,
+        failed: usize,
+        ignored: usize,
+        failures: &[crate::event::TestFailure],
+    ) -> Vec<String> {
+        let summary = format!("{passed} passed, {failed} failed, {ignored} ignored");
+        let (style, outcome) = match failed {
+            0 => (theme().status, "test run passed"),
+            _ => (theme().status_error, "test run failed"),
+        };
+        let mut entries = vec![format!("{}: {summary}", paint(style, outcome))];
+
+        for failure in failures {
+            let mut entry = paint(theme().status_error, &format!("✖ {}", failure.name));
+            for line in &failure.output {
+                entry.push_str(&format!("\n    {line}"));
+            }
+            entries.push(entry);
+        }
+
+        entries
+    }
+
+    /// Format a watcher event to display in the events tab, if
+    /// it's not already covered by the watchers own log messages.
+    fn format_event(event: &Event) -> Option<String> {
+        let msg = match event {
+            Event::BuildStarted { kind } => format!("{} started", kind.name()),
+            Event::BuildFinished {
+                kind,
+                success,
+                duration_ms,
+            } => format!(
+                "{} {} in {:.2}s",
+                kind.name(),
+                match (kind, success) {
+                    (BuildKind::Rust | BuildKind::Web, true) => "succeeded",
+                    (_, true) => "passed",
+                    (_, false) => "failed",
+                },
+                *duration_ms as f64 / 1000.0
+            ),
+            Event::ProcessExited {
+                name,
+                pid,
+                code: Some(code),
+            } => format!("process `{name}` exited with code {code} (PID: {pid})"),
+            Event::ProcessExited {
+                name,
+                pid,
+                code: None,
+            } => format!("process `{name}` was killed (PID: {pid})"),
+            _ => return None,
+        };
+
+        Some(Watcher::format_log_msg(&msg))
+    }
+}
+/// Implement the `EventSink` trait for thread safe `Tabs`, so the
+/// events published by `Watcher` show in the tab they belong to.
+impl EventSink for Arc<Mutex<Tabs>> {
+    fn publish(&mut self, event: &Event) {
+        let Ok(mut tabs) = self.lock() else {
+            return;
+        };
+
+        match event {
+            Event::Log { source, line } => {
+                tabs.add_log(Tab::All, *source, line.clone());
+                tabs.add_log(Tab::from_log_source(*source), *source, line.clone());
+            }
+            Event::Diagnostic(diagnostic) => {
+                tabs.add_log(
+                    Tab::Diagnostics,
+                    LogSource::Build,
+                    Tabs::format_diagnostic(diagnostic),
+                );
+            }
+            Event::TestResult {
+                name,
+                outcome: TestOutcome::Failed,
+            } => {
+                tabs.add_log(
+                    Tab::Tests,
+                    LogSource::Build,
+                    paint(theme().status_error, &format!("✖ {name}")),
+                );
+            }
+            Event::TestsFinished {
+                passed,
+                failed,
+                ignored,
+                failures,
+            } => {
+                tabs.tests.update(event);
+
+                // The tests tab only shows the latest test run
+                tabs.clear(Tab::Tests);
+                for entry in Tabs::format_test_results(*passed, *failed, *ignored, failures) {
+                    tabs.add_log(Tab::Tests, LogSource::Build, entry);
+                }
+            }
+            event => {
+                tabs.statuses.update(event);
+                tabs.tests.update(event);
+
+                // The diagnostics tab only shows the latest build (of the rust codebase)
+                if let Event::BuildStarted { kind } = event
+                    && *kind != BuildKind::Web
+                {
+                    tabs.clear(Tab::Diagnostics);
+                }
+                if let Event::ModeChanged { mode } = event {
+                    tabs.mode = *mode;
+                }
+                if let Event::BuildStarted {
+                    kind: BuildKind::Tests,
+                } = event
+                {
+                    tabs.clear(Tab::Tests);
+                    tabs.add_log(
+                        Tab::Tests,
+                        LogSource::Watcher,
+                        String::from("running the tests..."),
+                    );
+                }
+                if let Some(msg) = Tabs::format_event(event) {
+                    tabs.add_log(Tab::Events, LogSource::Watcher, msg);
+                }
+            }
+        }
+    }
+}
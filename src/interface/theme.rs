@@ -1,28 +1,359 @@
+use crate::{
+    config::{StyleConfig, ThemeConfig},
+    error::Error,
+};
 use ratatui::style::{Color, Modifier, Style};
+use std::{str::FromStr, sync::OnceLock};
+
+/// The theme in use, set once on start up (see `init`).
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Set the theme used by the interface.
+///
+/// NOTE: This can only be set once, later calls are ignored.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
 
+/// Get the theme used by the interface, the dark theme if not set.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::dark)
+}
+
+/// Wrap text in the ANSI escape codes of a style, for text shown
+/// amongst log messages (which are colored with ANSI escape codes).
+pub fn paint(style: Style, text: &str) -> String {
+    let mut codes = Vec::new();
+    if let Some(fg) = style.fg.and_then(|color| ansi_color(color, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|color| ansi_color(color, true)) {
+        codes.push(bg);
+    }
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+
+    match codes.is_empty() {
+        true => text.to_string(),
+        false => format!("\x1b[{}m{text}\x1b[0m", codes.join(";")),
+    }
+}
+
+/// Get the ANSI escape code (ex: `31`) setting the foreground
+/// (or background) to a color, `None` to leave it as is.
+fn ansi_color(color: Color, background: bool) -> Option<String> {
+    let (normal, bright) = match background {
+        true => (40, 100),
+        false => (30, 90),
+    };
+    let code = match color {
+        Color::Reset => return None,
+        Color::Black => normal,
+        Color::Red => normal + 1,
+        Color::Green => normal + 2,
+        Color::Yellow => normal + 3,
+        Color::Blue => normal + 4,
+        Color::Magenta => normal + 5,
+        Color::Cyan => normal + 6,
+        Color::Gray => normal + 7,
+        Color::DarkGray => bright,
+        Color::LightRed => bright + 1,
+        Color::LightGreen => bright + 2,
+        Color::LightYellow => bright + 3,
+        Color::LightBlue => bright + 4,
+        Color::LightMagenta => bright + 5,
+        Color::LightCyan => bright + 6,
+        Color::White => bright + 7,
+        Color::Indexed(idx) => return Some(format!("{};5;{idx}", normal + 8)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{r};{g};{b}", normal + 8)),
+    };
+
+    Some(code.to_string())
+}
+
+#[derive(Debug, Clone)]
 pub struct KeyBinding {
     pub key: Style,
     pub description: Style,
 }
 
+#[derive(Debug, Clone)]
 pub struct Theme {
     pub root: Style,
     pub app_title: Style,
+    pub command_bar: Style,
     pub key_binding: KeyBinding,
+    pub highlight: Style,
+    pub scrollbar: Style,
+    pub follow_indicator: Style,
+    pub selection: Style,
+    pub tab: Style,
+    pub tab_selected: Style,
+    pub tab_unread: Style,
+    pub pane_header: Style,
+    pub pane_header_focused: Style,
+    pub status: Style,
+    pub status_warning: Style,
+    pub status_error: Style,
+
+    /// Should the colors within log messages be displayed ?
+    pub log_colors: bool,
 }
+impl Theme {
+    /// The default dark theme.
+    pub fn dark() -> Self {
+        Self {
+            root: Style::new().bg(BLACK),
+            app_title: Style::new()
+                .fg(GREEN)
+                .bg(BLACK)
+                .add_modifier(Modifier::BOLD),
+            command_bar: Style::new().fg(Color::Indexed(236)).bg(Color::Indexed(232)),
+            key_binding: KeyBinding {
+                key: Style::new().fg(BLACK).bg(DARK_GRAY),
+                description: Style::new().fg(DARK_GRAY).bg(BLACK),
+            },
+            highlight: Style::new().bg(Color::DarkGray),
+            scrollbar: Style::new(),
+            follow_indicator: Style::new().fg(BLACK).bg(YELLOW),
+            selection: Style::new().bg(BLUE),
+            tab: Style::new().fg(DARK_GRAY).bg(BLACK),
+            tab_selected: Style::new()
+                .fg(GREEN)
+                .bg(BLACK)
+                .add_modifier(Modifier::BOLD),
+            tab_unread: Style::new().fg(YELLOW),
+            pane_header: Style::new().fg(DARK_GRAY).bg(BLACK),
+            pane_header_focused: Style::new().fg(BLACK).bg(GREEN),
+            status: Style::new().fg(GREEN).bg(BLACK),
+            status_warning: Style::new().fg(YELLOW).bg(BLACK),
+            status_error: Style::new().fg(RED).bg(BLACK).add_modifier(Modifier::BOLD),
+            log_colors: true,
+        }
+    }
 
-pub const THEME: Theme = Theme {
-    root: Style::new().bg(BLACK),
-    app_title: Style::new()
-        .fg(GREEN)
-        .bg(BLACK)
-        .add_modifier(Modifier::BOLD),
-    key_binding: KeyBinding {
-        key: Style::new().fg(BLACK).bg(DARK_GRAY),
-        description: Style::new().fg(DARK_GRAY).bg(BLACK),
-    },
-};
+    /// The light theme, for terminals with a light background.
+    pub fn light() -> Self {
+        Self {
+            root: Style::new().fg(Color::Black).bg(WHITE),
+            app_title: Style::new()
+                .fg(DARK_GREEN)
+                .bg(WHITE)
+                .add_modifier(Modifier::BOLD),
+            command_bar: Style::new().fg(Color::Black).bg(LIGHT_GRAY),
+            key_binding: KeyBinding {
+                key: Style::new().fg(WHITE).bg(MID_GRAY),
+                description: Style::new().fg(MID_GRAY).bg(WHITE),
+            },
+            highlight: Style::new().bg(LIGHT_GRAY),
+            scrollbar: Style::new().fg(MID_GRAY),
+            follow_indicator: Style::new().fg(Color::Black).bg(LIGHT_YELLOW),
+            selection: Style::new().bg(LIGHT_BLUE),
+            tab: Style::new().fg(MID_GRAY).bg(WHITE),
+            tab_selected: Style::new()
+                .fg(DARK_GREEN)
+                .bg(WHITE)
+                .add_modifier(Modifier::BOLD),
+            tab_unread: Style::new().fg(DARK_YELLOW),
+            pane_header: Style::new().fg(MID_GRAY).bg(WHITE),
+            pane_header_focused: Style::new().fg(WHITE).bg(DARK_GREEN),
+            status: Style::new().fg(DARK_GREEN).bg(WHITE),
+            status_warning: Style::new().fg(DARK_YELLOW).bg(WHITE),
+            status_error: Style::new()
+                .fg(DARK_RED)
+                .bg(WHITE)
+                .add_modifier(Modifier::BOLD),
+            log_colors: true,
+        }
+    }
+
+    /// The high contrast theme, using only the basic terminal colors.
+    pub fn high_contrast() -> Self {
+        Self {
+            root: Style::new().fg(Color::White).bg(Color::Black),
+            app_title: Style::new()
+                .fg(Color::LightGreen)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            command_bar: Style::new().fg(Color::White).bg(Color::Black),
+            key_binding: KeyBinding {
+                key: Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                description: Style::new().fg(Color::White).bg(Color::Black),
+            },
+            highlight: Style::new().fg(Color::Black).bg(Color::White),
+            scrollbar: Style::new().fg(Color::White),
+            follow_indicator: Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            selection: Style::new().fg(Color::Black).bg(Color::LightCyan),
+            tab: Style::new().fg(Color::White).bg(Color::Black),
+            tab_selected: Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+            tab_unread: Style::new()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            pane_header: Style::new().fg(Color::White).bg(Color::Black),
+            pane_header_focused: Style::new()
+                .fg(Color::Black)
+                .bg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+            status: Style::new().fg(Color::LightGreen).bg(Color::Black),
+            status_warning: Style::new().fg(Color::LightYellow).bg(Color::Black),
+            status_error: Style::new()
+                .fg(Color::LightRed)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            log_colors: true,
+        }
+    }
+
+    /// The colorless theme, used when the `NO_COLOR` environment
+    /// variable is set (see https://no-color.org), relying only on
+    /// text modifiers (bold, reversed) to tell things apart.
+    pub fn no_color() -> Self {
+        let reversed = Style::new().add_modifier(Modifier::REVERSED);
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+
+        Self {
+            root: Style::new(),
+            app_title: bold,
+            command_bar: Style::new(),
+            key_binding: KeyBinding {
+                key: reversed,
+                description: Style::new(),
+            },
+            highlight: reversed,
+            scrollbar: Style::new(),
+            follow_indicator: reversed,
+            selection: Style::new().add_modifier(Modifier::UNDERLINED),
+            tab: Style::new(),
+            tab_selected: reversed,
+            tab_unread: bold,
+            pane_header: Style::new(),
+            pane_header_focused: reversed,
+            status: Style::new(),
+            status_warning: bold,
+            status_error: reversed,
+            log_colors: false,
+        }
+    }
+
+    /// Create the theme described in the config, a preset with
+    /// any style overrides applied on top of it.
+    ///
+    /// NOTE: The `NO_COLOR` environment variable takes priority
+    /// over the config, always using the colorless theme.
+    pub fn from_config(config: &ThemeConfig) -> Result<Self, Error> {
+        if std::env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            return Ok(Self::no_color());
+        }
+
+        let mut theme = match config.preset.as_deref() {
+            None | Some("dark") => Self::dark(),
+            Some("light") => Self::light(),
+            Some("high-contrast") => Self::high_contrast(),
+            Some(preset) => {
+                return Err(Error::InvalidConfig(format!(
+                    "unknown theme preset `{preset}`, expected dark, light or high-contrast"
+                )));
+            }
+        };
+
+        for (name, style_config) in &config.styles {
+            let style = theme
+                .style_mut(name)
+                .ok_or_else(|| Error::InvalidConfig(format!("unknown theme style `{name}`")))?;
+            *style = Self::apply_style_config(*style, style_config)?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Get a style of the theme by its name in the config.
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        let style = match name {
+            "root" => &mut self.root,
+            "app_title" => &mut self.app_title,
+            "command_bar" => &mut self.command_bar,
+            "key" => &mut self.key_binding.key,
+            "key_description" => &mut self.key_binding.description,
+            "highlight" => &mut self.highlight,
+            "scrollbar" => &mut self.scrollbar,
+            "follow_indicator" => &mut self.follow_indicator,
+            "selection" => &mut self.selection,
+            "tab" => &mut self.tab,
+            "tab_selected" => &mut self.tab_selected,
+            "tab_unread" => &mut self.tab_unread,
+            "pane_header" => &mut self.pane_header,
+            "pane_header_focused" => &mut self.pane_header_focused,
+            "status" => &mut self.status,
+            "status_warning" => &mut self.status_warning,
+            "status_error" => &mut self.status_error,
+            _ => return None,
+        };
+
+        Some(style)
+    }
+
+    /// Apply a style override from the config on top of a style.
+    fn apply_style_config(mut style: Style, config: &StyleConfig) -> Result<Style, Error> {
+        let parse_color = |color: &str| {
+            Color::from_str(color)
+                .map_err(|_| Error::InvalidConfig(format!("invalid theme color `{color}`")))
+        };
+
+        if let Some(fg) = &config.fg {
+            style = style.fg(parse_color(fg)?);
+        }
+        if let Some(bg) = &config.bg {
+            style = style.bg(parse_color(bg)?);
+        }
+
+        let modifiers = [
+            (config.bold, Modifier::BOLD),
+            (config.italic, Modifier::ITALIC),
+            (config.underlined, Modifier::UNDERLINED),
+            (config.reversed, Modifier::REVERSED),
+        ];
+        for (enabled, modifier) in modifiers {
+            style = match enabled {
+                Some(true) => style.add_modifier(modifier),
+                Some(false) => style.remove_modifier(modifier),
+                None => style,
+            };
+        }
+
+        Ok(style)
+    }
+}
 
 const GREEN: Color = Color::Green;
+const YELLOW: Color = Color::Yellow;
+const RED: Color = Color::Red;
+const BLUE: Color = Color::Rgb(38, 58, 94);
 const BLACK: Color = Color::Rgb(8, 8, 8);
 const DARK_GRAY: Color = Color::Rgb(68, 68, 68);
+
+const WHITE: Color = Color::Rgb(250, 250, 250);
+const LIGHT_GRAY: Color = Color::Rgb(220, 220, 220);
+const MID_GRAY: Color = Color::Rgb(120, 120, 120);
+const DARK_GREEN: Color = Color::Rgb(0, 120, 40);
+const LIGHT_YELLOW: Color = Color::Rgb(250, 220, 100);
+const DARK_YELLOW: Color = Color::Rgb(170, 120, 0);
+const DARK_RED: Color = Color::Rgb(180, 20, 20);
+const LIGHT_BLUE: Color = Color::Rgb(190, 215, 250);
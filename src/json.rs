@@ -0,0 +1,54 @@
+use crate::{ansi, event::Event, sink::EventSink};
+use chrono::{SecondsFormat, Utc};
+use std::io::Write;
+
+/// A JSON event sink, which writes every event to stdout as
+/// newline delimited JSON for other tools (ex: editor plugins).
+///
+/// Each line is an object with the event name under `"event"`,
+/// a `"timestamp"`, and the fields of that event, for example:
+/// `{"event":"build_finished","kind":"rust","success":true,...}`
+///
+/// NOTE: ANSI colors are stripped from log lines, but kept in the
+/// `"rendered"` field of diagnostics for tools that can show them.
+#[derive(Debug, Default)]
+pub struct JsonWriter;
+impl JsonWriter {
+    /// Create a new `JsonWriter` instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl EventSink for JsonWriter {
+    fn publish(&mut self, event: &Event) {
+        let Some(line) = to_json_line(event) else {
+            return;
+        };
+
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{line}");
+        let _ = stdout.flush();
+    }
+}
+
+/// Serialize an event into a single line JSON object, with the
+/// event name under `"event"` and the time it was serialized
+/// under `"timestamp"`.
+///
+/// NOTE: ANSI colors are stripped from log lines.
+pub fn to_json_line(event: &Event) -> Option<String> {
+    let event = match event {
+        Event::Log { source, line } => &Event::Log {
+            source: *source,
+            line: ansi::strip(line),
+        },
+        event => event,
+    };
+    let Ok(serde_json::Value::Object(mut json)) = serde_json::to_value(event) else {
+        return None;
+    };
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    json.insert(String::from("timestamp"), timestamp.into());
+
+    Some(serde_json::Value::Object(json).to_string())
+}
@@ -0,0 +1,72 @@
+//! Hot reloader for Rust codebases, never waste time rebuilding again.
+//!
+//! Besides the `unlimited-ammo` binary, the watcher can be embedded in
+//! your own tooling, subscribing to the events it publishes:
+//!
+//! ```no_run
+//! use unlimited_ammo::WatcherBuilder;
+//!
+//! let mut watcher = WatcherBuilder::new().set_watch_interval(2).build()?;
+//!
+//! let events = watcher.subscribe();
+//! std::thread::spawn(move || {
+//!     for event in events {
+//!         println!("{event:?}");
+//!     }
+//! });
+//!
+//! watcher.start()?;
+//! # Ok::<(), unlimited_ammo::Error>(())
+//! ```
+//!
+//! Events can also be fed into an `EventSink` (ex: the `JsonWriter`)
+//! with `sink::spawn`, any number of subscribers can be attached.
+//!
+//! NOTE: The terminal user interface lives behind the (default) `tui`
+//! feature, disable default features to avoid pulling in ratatui.
+
+mod ansi;
+mod build_options;
+mod bus;
+mod cargo;
+pub mod config;
+mod error;
+pub mod event;
+mod hooks;
+#[cfg(feature = "tui")]
+pub mod interface;
+mod json;
+mod log_file;
+mod mode;
+mod process;
+mod ready;
+mod restart;
+pub mod sink;
+mod supervisor;
+mod terminal;
+mod testing;
+mod triggers;
+mod watcher;
+mod workspace;
+
+pub use build_options::{BuildChoices, BuildOptions, RunTarget};
+pub use bus::EventBus;
+pub use config::Config;
+pub use error::Error;
+pub use event::{BuildKind, Diagnostic, Event, LogSource, TestFailure, TestOutcome};
+pub use hooks::Hooks;
+pub use json::JsonWriter;
+pub use log_file::{LogFile, LogFormat};
+pub use mode::Mode;
+pub use process::Processes;
+pub use ready::ReadinessProbe;
+pub use restart::RestartPolicy;
+pub use sink::EventSink;
+pub use supervisor::Procfile;
+pub use terminal::Terminal;
+pub use triggers::TriggerRules;
+pub use watcher::{Watcher, WatcherBuilder, WatcherCommand};
+pub use workspace::Workspace;
+
+/// Unlimited Ammo Version
+pub static VERSION: &str = "v0.2.0";
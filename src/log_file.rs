@@ -0,0 +1,276 @@
+use crate::{
+    ansi,
+    error::Error,
+    event::{Event, LogSource},
+    json,
+    sink::EventSink,
+};
+use chrono::{SecondsFormat, Utc};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The number of rotated log files to keep around (ex: `ammo.log.1`
+/// through `ammo.log.5`), older ones are deleted.
+const MAX_ROTATED_FILES: usize = 5;
+
+/// The format log entries are written to the log file in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Plain text log lines, with ANSI colors stripped.
+    #[default]
+    Text,
+
+    /// Newline delimited JSON events, like the `JsonWriter`.
+    Jsonl,
+}
+
+/// A log file event sink, which mirrors every log entry into a file
+/// so it outlives the session (ex: after a crash or quitting).
+///
+/// NOTE: The file is rotated once it grows past `max_size` bytes,
+/// `ammo.log` is renamed to `ammo.log.1`, `ammo.log.1` to
+/// `ammo.log.2`, and so on.
+#[derive(Debug)]
+pub struct LogFile {
+    /// The path of the current log file.
+    path: PathBuf,
+
+    /// The format to write log entries in.
+    format: LogFormat,
+
+    /// How big (in bytes) the log file can grow before being rotated.
+    max_size: u64,
+
+    /// The currently open log file.
+    file: File,
+
+    /// The current size (in bytes) of the log file.
+    size: u64,
+
+    /// The number of builds started this session.
+    n_builds: usize,
+}
+impl LogFile {
+    /// Open (or create) a log file to append log entries to.
+    pub fn open(path: impl AsRef<Path>, format: LogFormat, max_size: u64) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let file = Self::open_file(&path)?;
+        let size = file.metadata()?.len();
+
+        let mut log_file = Self {
+            path,
+            format,
+            max_size,
+            file,
+            size,
+            n_builds: 0,
+        };
+        if format == LogFormat::Text {
+            log_file.write_header("session started");
+        }
+
+        Ok(log_file)
+    }
+
+    /// Open a file for appending, creating it if it doesn't exist.
+    fn open_file(path: &Path) -> Result<File, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(file)
+    }
+
+    /// Write a header line marking the start of a section of the log
+    /// (ex: a session or build cycle), making the log easier to skim.
+    fn write_header(&mut self, title: &str) {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        self.write(&format!("\n===== [{timestamp}] {title} =====\n"));
+    }
+
+    /// Write a log entry to the file, rotating it first if needed.
+    ///
+    /// NOTE: Failing to write isn't worth bringing down the watcher,
+    /// so errors are reported through `log` and otherwise ignored.
+    fn write(&mut self, entry: &str) {
+        if self.size > 0
+            && self.size + entry.len() as u64 > self.max_size
+            && let Err(e) = self.rotate()
+        {
+            log::error!("Error rotating log file: {e}");
+        }
+
+        match self.file.write_all(entry.as_bytes()) {
+            Ok(_) => self.size += entry.len() as u64,
+            Err(e) => log::error!("Error writing to log file: {e}"),
+        }
+    }
+
+    /// Rotate the log files, shifting each one up a number and
+    /// starting a fresh (empty) log file.
+    fn rotate(&mut self) -> Result<(), Error> {
+        for idx in (1..MAX_ROTATED_FILES).rev() {
+            let from = self.rotated_path(idx);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(idx + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))?;
+
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+
+    /// Get the paths of the log file and of every rotated log file,
+    /// whether they exist yet or not (ex: to not watch them).
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let rotated = (1..=MAX_ROTATED_FILES).map(|idx| self.rotated_path(idx));
+        std::iter::once(self.path.clone()).chain(rotated).collect()
+    }
+
+    /// Get the path of a rotated log file (ex: `ammo.log.2`).
+    fn rotated_path(&self, idx: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{idx}"));
+        PathBuf::from(path)
+    }
+
+    /// Format a log message as a plain text log entry, prefixing each
+    /// line with the time and where it originated from.
+    fn format_text_entry(source: LogSource, line: &str) -> String {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let source = source.name();
+
+        ansi::strip(line)
+            .lines()
+            .map(|line| format!("{timestamp} {source:<7} {line}\n"))
+            .collect()
+    }
+}
+impl EventSink for LogFile {
+    fn publish(&mut self, event: &Event) {
+        match self.format {
+            LogFormat::Jsonl => {
+                if let Some(line) = json::to_json_line(event) {
+                    self.write(&format!("{line}\n"));
+                }
+            }
+            LogFormat::Text => match event {
+                Event::BuildStarted { kind } => {
+                    self.n_builds += 1;
+                    let header = format!("build #{} started ({})", self.n_builds, kind.name());
+                    self.write_header(&header);
+                }
+                Event::Log { source, line } => {
+                    self.write(&Self::format_text_entry(*source, line));
+                }
+                _ => {}
+            },
+        }
+        let _ = self.file.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::BuildKind;
+
+    /// Get the path of a fresh log file in a directory of it's own.
+    fn log_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "unlimited-ammo-log-file-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir.join("ammo.log")
+    }
+
+    fn log(line: &str) -> Event {
+        Event::Log {
+            source: LogSource::Stdout,
+            line: line.to_string(),
+        }
+    }
+
+    #[test]
+    fn rotates_past_the_max_size() {
+        let path = log_path("rotates");
+        let mut log_file = LogFile::open(&path, LogFormat::Jsonl, 100).unwrap();
+
+        log_file.publish(&log(&"a".repeat(50)));
+        assert!(!log_file.rotated_path(1).exists());
+
+        log_file.publish(&log(&"b".repeat(50)));
+        let rotated = std::fs::read_to_string(log_file.rotated_path(1)).unwrap();
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(rotated.contains(&"a".repeat(50)));
+        assert!(!current.contains(&"a".repeat(50)));
+        assert!(current.contains(&"b".repeat(50)));
+    }
+
+    #[test]
+    fn keeps_at_most_the_max_rotated_files() {
+        let path = log_path("keeps");
+        let mut log_file = LogFile::open(&path, LogFormat::Jsonl, 1).unwrap();
+
+        for idx in 0..MAX_ROTATED_FILES + 3 {
+            log_file.publish(&log(&format!("entry {idx}")));
+        }
+
+        let n_files = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(n_files, MAX_ROTATED_FILES + 1);
+        assert!(!log_file.rotated_path(MAX_ROTATED_FILES + 1).exists());
+
+        // NOTE: The oldest entries were deleted, the newest one is in the current file.
+        let oldest = std::fs::read_to_string(log_file.rotated_path(MAX_ROTATED_FILES)).unwrap();
+        assert!(oldest.contains("entry 2"));
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(current.contains(&format!("entry {}", MAX_ROTATED_FILES + 2)));
+    }
+
+    #[test]
+    fn writes_text_entries() {
+        let path = log_path("text");
+        let mut log_file = LogFile::open(&path, LogFormat::Text, 1024 * 1024).unwrap();
+
+        log_file.publish(&Event::BuildStarted {
+            kind: BuildKind::Rust,
+        });
+        log_file.publish(&Event::Log {
+            source: LogSource::Watcher,
+            line: String::from("\x1b[32mfirst\x1b[0m\nsecond"),
+        });
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+        assert!(lines[0].starts_with("===== [") && lines[0].ends_with("] session started ====="));
+        assert!(lines[1].ends_with("] build #1 started (rust build) ====="));
+        assert!(lines[2].ends_with(" watcher first"));
+        assert!(lines[3].ends_with(" watcher second"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn writes_jsonl_entries() {
+        let path = log_path("jsonl");
+        let mut log_file = LogFile::open(&path, LogFormat::Jsonl, 1024 * 1024).unwrap();
+
+        log_file.publish(&Event::Log {
+            source: LogSource::Stderr,
+            line: String::from("\x1b[31moops\x1b[0m"),
+        });
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(json["source"], "stderr");
+        assert_eq!(json["line"], "oops");
+        assert!(json["timestamp"].is_string());
+    }
+}
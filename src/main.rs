@@ -1,49 +1,113 @@
-mod error;
-mod interface;
-mod watcher;
+mod cli;
 
-use self::error::Error;
 use crossterm::{
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::{
-    io::stdout,
-    process::Child,
+    io::{IsTerminal, stdout},
     sync::{Arc, Mutex},
     thread,
 };
-
-/// Unlimited Ammo Version
-pub static VERSION: &str = "v0.2.0";
+use unlimited_ammo::{
+    Config, Error, EventSink, Hooks, JsonWriter, LogFile, Processes, Procfile, ReadinessProbe,
+    RestartPolicy, Terminal, TriggerRules, Watcher, WatcherBuilder, interface, sink,
+};
 
 fn main() -> Result<(), Error> {
+    let args = cli::Args::parse()?;
+    let config = match &args.config {
+        Some(path) => Config::load_from(path)?,
+        None => Config::load()?,
+    };
+
+    // Fallback to plain output when there's no terminal to draw
+    // the interface in (ex: piped output, containers, or CI).
+    let use_tui = !args.json && !args.no_tui && stdout().is_terminal();
+
+    // The running processes, shared so the interface can write
+    // to the stdin of the app, and kill them when the app quits
+    let processes: Arc<Mutex<Processes>> = Arc::default();
+
+    // Mirror the log entries into a file if requested
+    let log_file = args
+        .log_file
+        .as_ref()
+        .map(|path| LogFile::open(path, args.log_format, args.log_max_size))
+        .transpose()?;
+
+    let mut builder = WatcherBuilder::new()
+        .set_watch_interval(2)
+        .set_processes(Arc::clone(&processes))
+        .set_pipe_stdin(use_tui)
+        .set_pty(args.pty)
+        .set_restart_policy(RestartPolicy::from_config(&config.run)?)
+        .set_readiness_probe(ReadinessProbe::from_config(&config.run.ready)?)
+        .set_hooks(Hooks::from_config(&config.hooks))
+        .set_triggers(TriggerRules::from_config(&config.triggers)?)
+        .set_procfile(Procfile::from_config(&config.processes)?)
+        .set_mode(args.mode)
+        .set_clippy_args(config.clippy.args.clone());
+
+    // NOTE: The log file is written to as the project is watched, so
+    // it (and it's rotated copies) would trigger a rebuild on every write.
+    for path in log_file.iter().flat_map(LogFile::paths) {
+        builder = builder.ignore_path(path);
+    }
+    let watcher = builder.build()?;
+
+    if let Some(log_file) = log_file {
+        sink::spawn(watcher.subscribe(), log_file);
+    }
+
+    if use_tui {
+        run_tui(watcher, processes, &config)
+    } else if args.json {
+        run_headless(watcher, JsonWriter::new())
+    } else {
+        run_headless(watcher, Terminal::new())
+    }
+}
+
+/// Run the watcher with the terminal user interface.
+fn run_tui(
+    mut watcher: Watcher,
+    processes: Arc<Mutex<Processes>>,
+    config: &Config,
+) -> Result<(), Error> {
+    interface::theme::init(interface::Theme::from_config(&config.theme)?);
+    let keymap = interface::Keymap::from_config(&config.keys)?;
+
     // Setup the terminal user interface
     let terminal = ratatui::init();
     execute!(stdout(), EnterAlternateScreen).expect("failed to enter alternate screen");
 
-    // Create a thread safe instance of the display interface
-    let display = Arc::new(Mutex::new(interface::Display::new()));
-    let display_clone = Arc::clone(&display);
+    // Create a thread safe instance of the interface tabs
+    // which are fed the events published by the watcher
+    let tabs = Arc::new(Mutex::new(interface::Tabs::new(
+        interface::PaneLayout::load(),
+    )));
+    sink::spawn(watcher.subscribe(), Arc::clone(&tabs));
+    let commands = watcher.commands();
 
     // Spawn the watcher in a new thread
     // so it doesn't block the interface
-    let build_process: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
-    let build_process_clone = Arc::clone(&build_process);
     thread::spawn(move || {
-        watcher::WatcherBuilder::new()
-            .set_watch_interval(2)
-            .set_build_process(build_process_clone)
-            .set_display(display_clone)
-            .build()
-            .expect("Failed to build project watcher")
-            .start()
-            .expect("Watcher failed to start");
+        watcher.start().expect("Watcher failed to start");
     });
 
     // Run the interface application
-    let app_result = interface::App::new(display, build_process).run(terminal);
+    let app_result =
+        interface::App::new(tabs, processes, keymap, commands, config.mouse.enabled).run(terminal);
     execute!(stdout(), LeaveAlternateScreen).expect("failed to leave alternate screen");
     ratatui::restore();
     app_result
 }
+
+/// Run the watcher without the terminal user interface, feeding
+/// events straight into the given sink (ex: plain stdout/stderr or JSON).
+fn run_headless(mut watcher: Watcher, sink: impl EventSink + 'static) -> Result<(), Error> {
+    sink::spawn(watcher.subscribe(), sink);
+
+    watcher.start()
+}
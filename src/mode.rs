@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+/// What the `Watcher` does with the codebase when it changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Build the app, and (re)start it.
+    #[default]
+    Run,
+
+    /// Run the tests, with `cargo nextest` when it's installed
+    /// or `cargo test` otherwise, without running the app.
+    Test,
+
+    /// Only check the codebase compiles (`cargo check`), without running anything.
+    Check,
+
+    /// Lint the codebase with `cargo clippy`, without running anything.
+    Clippy,
+}
+impl Mode {
+    /// Every mode, in the order they're cycled through.
+    pub const ALL: [Mode; 4] = [Mode::Run, Mode::Test, Mode::Check, Mode::Clippy];
+
+    /// Get the name of the mode (ex: `test`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Run => "run",
+            Mode::Test => "test",
+            Mode::Check => "check",
+            Mode::Clippy => "clippy",
+        }
+    }
+
+    /// Get a mode by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Get the mode following this one, wrapping around to the first.
+    pub fn next(&self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|mode| mode == self)
+            .unwrap_or_default();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
@@ -0,0 +1,298 @@
+use portable_pty::{CommandBuilder, MasterPty, PtySize};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Sender},
+};
+
+/// The name of the app built from the codebase, among the supervised processes.
+pub const APP: &str = "app";
+
+/// The output of a spawned `Process`, read line by line.
+pub enum Output {
+    /// The stdout and stderr of a process with piped stdio.
+    Piped {
+        stdout: Box<dyn Read + Send>,
+        stderr: Box<dyn Read + Send>,
+    },
+
+    /// The output of a process in a pseudo-terminal, where
+    /// stdout and stderr are one and the same.
+    Pty(Box<dyn Read + Send>),
+}
+
+/// A running process (ex: the app), either with piped
+/// stdio or inside a pseudo-terminal (PTY).
+pub struct Process {
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+
+    /// Where to send what's typed into the process, written to
+    /// it's stdin by a thread of it's own (see `spawn_stdin_writer`).
+    stdin: Option<Sender<Vec<u8>>>,
+
+    /// The controlling side of the pseudo-terminal, `None` when piped.
+    pty: Option<Box<dyn MasterPty + Send>>,
+}
+impl Process {
+    /// Spawn a program with piped stdio.
+    ///
+    /// NOTE: When `pipe_stdin` is false the process shares our stdin. With
+    /// `process_group` it leads it's own process group, so whatever it spawns
+    /// (ex: `cargo run` spawning the binary) is killed with it, but it no
+    /// longer gets the signals of the terminal (ex: Ctrl-C), so something
+    /// has to kill it on exit (ex: the interface).
+    pub fn spawn_piped(
+        program: &Path,
+        args: &[&str],
+        envs: &[(&str, &str)],
+        pipe_stdin: bool,
+        process_group: bool,
+    ) -> std::io::Result<(Self, Output)> {
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .envs(envs.iter().copied())
+            .stdin(match pipe_stdin {
+                true => Stdio::piped(),
+                false => Stdio::inherit(),
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // NOTE: A process reading the terminal has to stay in our process group.
+        #[cfg(unix)]
+        if pipe_stdin && process_group {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        let mut child = command.spawn()?;
+
+        // SAFETY: These unwraps are safe since stdout and stderr were piped above
+        let output = Output::Piped {
+            stdout: Box::new(child.stdout.take().unwrap()),
+            stderr: Box::new(child.stderr.take().unwrap()),
+        };
+        let stdin = child.stdin.take().map(Self::spawn_stdin_writer);
+
+        let process = Self {
+            child: Box::new(child),
+            stdin,
+            pty: None,
+        };
+
+        Ok((process, output))
+    }
+
+    /// Spawn a program inside a new pseudo-terminal, so it sees a
+    /// real terminal of the given size (ex: for colors, progress bars).
+    pub fn spawn_pty(
+        program: &Path,
+        args: &[&str],
+        envs: &[(&str, &str)],
+        (columns, rows): (u16, u16),
+    ) -> std::io::Result<(Self, Output)> {
+        let pair = portable_pty::native_pty_system()
+            .openpty(Self::pty_size(columns, rows))
+            .map_err(std::io::Error::other)?;
+
+        let mut command = CommandBuilder::new(program);
+        command.args(args);
+        command.cwd(std::env::current_dir()?);
+        command.env("TERM", "xterm-256color");
+        for (key, value) in envs {
+            command.env(key, value);
+        }
+
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(std::io::Error::other)?;
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(std::io::Error::other)?;
+        let writer = pair.master.take_writer().map_err(std::io::Error::other)?;
+
+        let process = Self {
+            child,
+            stdin: Some(Self::spawn_stdin_writer(writer)),
+            pty: Some(pair.master),
+        };
+
+        Ok((process, Output::Pty(reader)))
+    }
+
+    /// Get the size of a pseudo-terminal.
+    fn pty_size(columns: u16, rows: u16) -> PtySize {
+        PtySize {
+            rows: rows.max(1),
+            cols: columns.max(1),
+            pixel_width: 0,
+            pixel_height: 0,
+        }
+    }
+
+    /// Get the ID of the process.
+    pub fn id(&self) -> u32 {
+        self.child.process_id().unwrap_or_default()
+    }
+
+    /// Is the process running inside a pseudo-terminal ?
+    pub fn is_pty(&self) -> bool {
+        self.pty.is_some()
+    }
+
+    /// Kill the process, along with it's process group when it leads one.
+    ///
+    /// NOTE: Processes in a pseudo-terminal lead their own session.
+    pub fn kill(&mut self) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            let pid = self.id() as libc::pid_t;
+            // SAFETY: These only send a signal, checking the process leads
+            // it's own group first so we never signal our own group.
+            unsafe {
+                if pid > 0 && libc::getpgid(pid) == pid {
+                    libc::kill(-pid, libc::SIGKILL);
+                }
+            }
+        }
+
+        self.child.kill()
+    }
+
+    /// Wait for the process to exit, returning its exit
+    /// code (`None` when it was killed by a signal).
+    pub fn wait(&mut self) -> std::io::Result<Option<i32>> {
+        self.child.wait().map(Self::exit_code)
+    }
+
+    /// Check if the process exited without waiting for it, returning
+    /// its exit code (`None` when it was killed by a signal) if it did.
+    pub fn try_wait(&mut self) -> std::io::Result<Option<Option<i32>>> {
+        self.child
+            .try_wait()
+            .map(|status| status.map(Self::exit_code))
+    }
+
+    /// Get the exit code of an exited process.
+    fn exit_code(status: portable_pty::ExitStatus) -> Option<i32> {
+        match status.signal() {
+            Some(_) => None,
+            None => Some(status.exit_code() as i32),
+        }
+    }
+
+    /// Spawn a thread writing what's sent to it to the stdin of a process.
+    ///
+    /// NOTE: Writing blocks once the pipe is full (ex: the process stopped
+    /// reading it's stdin), which would otherwise freeze the caller.
+    fn spawn_stdin_writer(mut stdin: impl Write + Send + 'static) -> Sender<Vec<u8>> {
+        let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+        std::thread::spawn(move || {
+            for bytes in receiver {
+                if stdin.write_all(&bytes).and_then(|_| stdin.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        sender
+    }
+
+    /// Write to the stdin of the process, without waiting for it to be written.
+    pub fn write_stdin(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let stdin = self.stdin.as_ref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "the app's stdin isn't piped",
+            )
+        })?;
+
+        stdin.send(bytes.to_vec()).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::BrokenPipe, "the app's stdin was closed")
+        })
+    }
+
+    /// Resize the pseudo-terminal of the process (does nothing if piped).
+    pub fn resize(&self, columns: u16, rows: u16) -> std::io::Result<()> {
+        match &self.pty {
+            Some(pty) => pty
+                .resize(Self::pty_size(columns, rows))
+                .map_err(std::io::Error::other),
+            None => Ok(()),
+        }
+    }
+}
+/// Implement the debug trait for `Process`
+impl std::fmt::Debug for Process {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Process")
+            .field("id", &self.id())
+            .field("pty", &self.is_pty())
+            .finish()
+    }
+}
+
+/// The running processes supervised by a `Watcher`, by name (ex: the app, a worker).
+#[derive(Debug, Default)]
+pub struct Processes {
+    running: BTreeMap<String, Process>,
+
+    /// The commands ran from the interface (ex: `:exec cargo test`), by PID.
+    commands: BTreeMap<u32, Process>,
+}
+impl Processes {
+    /// Get a running process by name.
+    pub fn get(&self, name: &str) -> Option<&Process> {
+        self.running.get(name)
+    }
+
+    /// Get a running process by name, to write to it or kill it.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Process> {
+        self.running.get_mut(name)
+    }
+
+    /// Is a process with this name and PID still running ?
+    ///
+    /// NOTE: Once a process is replaced (ex: restarted) it's PID no longer matches.
+    pub fn is_running(&self, name: &str, pid: u32) -> bool {
+        self.get(name).is_some_and(|process| process.id() == pid)
+    }
+
+    /// Add a running process, returning the one it replaced (if any).
+    pub fn insert(&mut self, name: &str, process: Process) -> Option<Process> {
+        self.running.insert(name.to_string(), process)
+    }
+
+    /// Take a running process out, to stop it.
+    pub fn remove(&mut self, name: &str) -> Option<Process> {
+        self.running.remove(name)
+    }
+
+    /// Add a running command, until it exits.
+    pub fn insert_command(&mut self, command: Process) {
+        self.commands.insert(command.id(), command);
+    }
+
+    /// Take a running command out, once it exited.
+    pub fn remove_command(&mut self, pid: u32) -> Option<Process> {
+        self.commands.remove(&pid)
+    }
+
+    /// Take every running process out (ex: to stop them on shutdown), commands included.
+    pub fn take_all(&mut self) -> Vec<Process> {
+        std::mem::take(&mut self.running)
+            .into_values()
+            .chain(std::mem::take(&mut self.commands).into_values())
+            .collect()
+    }
+
+    /// Iterate over the running processes.
+    pub fn iter(&self) -> impl Iterator<Item = &Process> {
+        self.running.values()
+    }
+}
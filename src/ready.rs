@@ -0,0 +1,148 @@
+use crate::{ansi, config::ReadyConfig, error::Error};
+use regex::Regex;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// How long to wait on a connection (or response) when probing the app.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How to tell when the app is ready (ex: accepting connections),
+/// checked every `interval` after it was started.
+#[derive(Debug, Clone)]
+pub struct ReadinessProbe {
+    /// What to check.
+    pub check: ReadinessCheck,
+
+    /// How often to check it.
+    pub interval: Duration,
+}
+impl ReadinessProbe {
+    /// Create the readiness probe described in the `[run.ready]`
+    /// section of the config, if there is one.
+    pub fn from_config(config: &ReadyConfig) -> Result<Option<Self>, Error> {
+        let check = match (&config.tcp, &config.http, &config.log) {
+            (None, None, None) => return Ok(None),
+            (Some(address), None, None) => ReadinessCheck::Tcp(Self::parse_address(address)),
+            (None, Some(url), None) => Self::parse_url(url)?,
+            (None, None, Some(pattern)) => {
+                ReadinessCheck::Log(Regex::new(pattern).map_err(|e| {
+                    Error::InvalidConfig(format!("invalid readiness log pattern: {e}"))
+                })?)
+            }
+            _ => {
+                return Err(Error::InvalidConfig(String::from(
+                    "only one of tcp, http or log can be set as the readiness probe",
+                )));
+            }
+        };
+
+        Ok(Some(Self {
+            check,
+            interval: Duration::from_millis(config.interval_ms.max(1)),
+        }))
+    }
+
+    /// Get the address of a TCP probe, defaulting to localhost if it's only a port.
+    fn parse_address(address: &str) -> String {
+        match address.parse::<u16>() {
+            Ok(port) => format!("localhost:{port}"),
+            Err(_) => address.to_string(),
+        }
+    }
+
+    /// Parse the URL of an HTTP probe (ex: `http://localhost:8080/health`).
+    ///
+    /// NOTE: Only plain `http` is supported, it's meant for the app running locally.
+    fn parse_url(url: &str) -> Result<ReadinessCheck, Error> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            Error::InvalidConfig(format!("readiness URL `{url}` must start with http://"))
+        })?;
+        let (host, path) = match rest.find('/') {
+            Some(idx) => rest.split_at(idx),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            return Err(Error::InvalidConfig(format!(
+                "readiness URL `{url}` is missing a host"
+            )));
+        }
+        let address = match host.contains(':') {
+            true => host.to_string(),
+            false => format!("{host}:80"),
+        };
+
+        Ok(ReadinessCheck::Http {
+            address,
+            host: host.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+/// What a readiness probe checks.
+#[derive(Debug, Clone)]
+pub enum ReadinessCheck {
+    /// The address accepts connections.
+    Tcp(String),
+
+    /// A GET request responds with a 2xx status.
+    Http {
+        address: String,
+        host: String,
+        path: String,
+    },
+
+    /// The app logs a matching line.
+    Log(Regex),
+}
+impl ReadinessCheck {
+    /// Check if the app is ready now (always false for `Log`, see `matches`).
+    pub fn is_ready(&self) -> bool {
+        match self {
+            ReadinessCheck::Tcp(address) => Self::connect(address).is_some(),
+            ReadinessCheck::Http {
+                address,
+                host,
+                path,
+            } => Self::get(address, host, path).is_some_and(|status| (200..300).contains(&status)),
+            ReadinessCheck::Log(_) => false,
+        }
+    }
+
+    /// Does a line logged by the app say it's ready ?
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            ReadinessCheck::Log(regex) => regex.is_match(&ansi::strip(line)),
+            _ => false,
+        }
+    }
+
+    /// Connect to an address, trying everything it resolves to
+    /// (ex: `localhost` is both `127.0.0.1` and `::1`).
+    fn connect(address: &str) -> Option<TcpStream> {
+        address
+            .to_socket_addrs()
+            .ok()?
+            .find_map(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).ok())
+    }
+
+    /// Send a GET request, returning the status code of the response.
+    fn get(address: &str, host: &str, path: &str) -> Option<u16> {
+        let mut stream = Self::connect(address)?;
+        stream.set_read_timeout(Some(PROBE_TIMEOUT)).ok()?;
+        stream.set_write_timeout(Some(PROBE_TIMEOUT)).ok()?;
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n"
+        )
+        .ok()?;
+
+        // NOTE: The status line looks like `HTTP/1.1 200 OK`.
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line).ok()?;
+        status_line.split_whitespace().nth(1)?.parse().ok()
+    }
+}
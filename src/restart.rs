@@ -0,0 +1,86 @@
+use crate::{config::RunConfig, error::Error};
+use std::time::Duration;
+
+/// The longest delay between restarts, however many there were in a row.
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// How long the app has to stay up to not be considered crash looping,
+/// starting the count of restarts in a row over.
+pub const STABLE_AFTER: Duration = Duration::from_secs(10);
+
+/// When to restart the app after it exits on it's own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Restart {
+    /// Leave it stopped until the next build.
+    #[default]
+    Never,
+
+    /// Only when it failed (ex: panicked, or was killed by a signal).
+    OnFailure,
+
+    /// Whatever the exit code was.
+    Always,
+}
+
+/// How to restart the app when it exits on it's own, waiting longer
+/// (exponential backoff) every time it's restarted in a row, until
+/// giving up on it as a crash loop.
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// When to restart the app.
+    pub restart: Restart,
+
+    /// How many times in a row the app is restarted before giving up.
+    pub max_restarts: u32,
+
+    /// How long to wait before the first restart in a row.
+    pub delay: Duration,
+}
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        let config = RunConfig::default();
+
+        Self {
+            restart: Restart::default(),
+            max_restarts: config.max_restarts,
+            delay: Duration::from_millis(config.restart_delay_ms),
+        }
+    }
+}
+impl RestartPolicy {
+    /// Create the restart policy described in the `[run]` section of the config.
+    pub fn from_config(config: &RunConfig) -> Result<Self, Error> {
+        let restart = match config.restart.as_deref() {
+            None | Some("never") => Restart::Never,
+            Some("on-failure") => Restart::OnFailure,
+            Some("always") => Restart::Always,
+            Some(restart) => {
+                return Err(Error::InvalidConfig(format!(
+                    "unknown restart policy `{restart}`, expected never, on-failure or always"
+                )));
+            }
+        };
+
+        Ok(Self {
+            restart,
+            max_restarts: config.max_restarts,
+            delay: Duration::from_millis(config.restart_delay_ms),
+        })
+    }
+
+    /// Should the app be restarted after exiting with this
+    /// code (`None` when it was killed by a signal) ?
+    pub fn should_restart(&self, code: Option<i32>) -> bool {
+        match self.restart {
+            Restart::Never => false,
+            Restart::OnFailure => code != Some(0),
+            Restart::Always => true,
+        }
+    }
+
+    /// How long to wait before the nth restart in a row (starting at 1).
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.delay.saturating_mul(factor).min(MAX_RESTART_DELAY)
+    }
+}
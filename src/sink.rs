@@ -0,0 +1,24 @@
+use crate::event::Event;
+use std::{
+    sync::mpsc::Receiver,
+    thread::{self, JoinHandle},
+};
+
+/// Something consuming the events published by `Watcher`,
+/// like the terminal user interface or plain stdout/stderr.
+pub trait EventSink: Send {
+    /// Handle an event published by `Watcher`.
+    fn publish(&mut self, event: &Event);
+}
+
+/// Spawn a thread feeding each event received from a
+/// subscription (see `EventBus::subscribe`) into a sink.
+///
+/// NOTE: The thread finishes once the `Watcher` is dropped.
+pub fn spawn<S: EventSink + 'static>(events: Receiver<Event>, mut sink: S) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for event in events {
+            sink.publish(&event);
+        }
+    })
+}
@@ -0,0 +1,138 @@
+use crate::{config::ProcessConfig, error::Error, process::APP, triggers::PathGlob};
+use std::collections::{HashMap, HashSet};
+
+/// The colors given in order to the log prefixes of processes without one.
+const PREFIX_COLORS: [&str; 6] = ["cyan", "yellow", "magenta", "green", "blue", "red"];
+
+/// A long-running process supervised along with the app (ex: a worker).
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    /// The name of the process (ex: `worker`).
+    pub name: String,
+
+    /// The shell command running the process.
+    pub command: String,
+
+    /// Restart the process on changes to paths matching these globs.
+    restart_on: Vec<PathGlob>,
+}
+impl ProcessSpec {
+    /// Should the process be restarted for changes to a set of paths ?
+    pub fn is_restarted_by(&self, paths: &[String]) -> bool {
+        paths
+            .iter()
+            .any(|path| self.restart_on.iter().any(|glob| glob.matches(path)))
+    }
+}
+
+/// The processes supervised along with the app, Procfile style,
+/// and the prefixes of their logs.
+#[derive(Debug, Clone, Default)]
+pub struct Procfile {
+    processes: Vec<ProcessSpec>,
+
+    /// The (colored) prefix of the logs of each process, the app included.
+    ///
+    /// NOTE: Without other processes, the logs of the app aren't prefixed.
+    prefixes: HashMap<String, String>,
+}
+impl Procfile {
+    /// Create the processes described by the `[[processes]]` of the config.
+    pub fn from_config(config: &[ProcessConfig]) -> Result<Self, Error> {
+        if config.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut names = HashSet::from([APP]);
+        let mut processes = Vec::new();
+        let mut labels = vec![(APP, String::from(APP), PREFIX_COLORS[0])];
+        for (i, process) in config.iter().enumerate() {
+            if process.name.is_empty() || !names.insert(process.name.as_str()) {
+                return Err(Error::InvalidConfig(format!(
+                    "a process can't be named `{}`, the names of processes must be unique (and `{APP}` is the app)",
+                    process.name
+                )));
+            }
+
+            let color = process
+                .color
+                .as_deref()
+                .unwrap_or(PREFIX_COLORS[(i + 1) % PREFIX_COLORS.len()]);
+            let label = process.prefix.clone().unwrap_or(process.name.clone());
+            labels.push((&process.name, label, color));
+
+            processes.push(ProcessSpec {
+                name: process.name.clone(),
+                command: process.command.clone(),
+                restart_on: process
+                    .restart_on
+                    .iter()
+                    .map(|glob| PathGlob::new(glob))
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+
+        // NOTE: Pad the prefixes, so the logs of every process line up.
+        let width = labels
+            .iter()
+            .map(|(_, label, _)| label.chars().count())
+            .max()
+            .unwrap_or_default();
+        let mut prefixes = HashMap::new();
+        for (name, label, color) in labels {
+            let code = Self::color_code(color).ok_or_else(|| {
+                Error::InvalidConfig(format!(
+                    "unknown color `{color}` for the process `{name}`, expected a color name (ex: `yellow`, `bright-blue`)"
+                ))
+            })?;
+            prefixes.insert(
+                name.to_string(),
+                format!("\x1b[{code}m{label:<width$} |\x1b[0m "),
+            );
+        }
+
+        Ok(Self {
+            processes,
+            prefixes,
+        })
+    }
+
+    /// Get the ANSI code of a color name (ex: `yellow`, `bright-blue`).
+    fn color_code(name: &str) -> Option<u8> {
+        let (bright, name) = match name.strip_prefix("bright-") {
+            Some(name) => (true, name),
+            None => (false, name),
+        };
+        let code = match name {
+            "black" => 30,
+            "red" => 31,
+            "green" => 32,
+            "yellow" => 33,
+            "blue" => 34,
+            "magenta" => 35,
+            "cyan" => 36,
+            "white" => 37,
+            _ => return None,
+        };
+
+        Some(if bright { code + 60 } else { code })
+    }
+
+    /// Get the processes supervised along with the app.
+    pub fn processes(&self) -> &[ProcessSpec] {
+        &self.processes
+    }
+
+    /// Get a process supervised along with the app by name.
+    pub fn get(&self, name: &str) -> Option<&ProcessSpec> {
+        self.processes.iter().find(|process| process.name == name)
+    }
+
+    /// Get the prefix of the logs of a process (or the app), empty when there's none.
+    pub fn prefix(&self, name: &str) -> &str {
+        self.prefixes
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
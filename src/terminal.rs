@@ -0,0 +1,53 @@
+use crate::{
+    ansi,
+    event::{Event, LogSource},
+    sink::EventSink,
+};
+use std::io::{IsTerminal, Write};
+
+/// A plain terminal event sink, which writes log messages straight
+/// to stdout/stderr without running the terminal user interface.
+///
+/// NOTE: ANSI colors are only kept when the stream is a terminal (and
+/// `NO_COLOR` isn't set), otherwise they're stripped so pipes and CI
+/// logs stay readable.
+#[derive(Debug)]
+pub struct Terminal {
+    /// Is stdout a terminal (can it render colors) ?
+    stdout_is_terminal: bool,
+
+    /// Is stderr a terminal (can it render colors) ?
+    stderr_is_terminal: bool,
+}
+impl Terminal {
+    /// Create a new `Terminal` instance
+    pub fn new() -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|no_color| !no_color.is_empty());
+
+        Self {
+            stdout_is_terminal: std::io::stdout().is_terminal() && !no_color,
+            stderr_is_terminal: std::io::stderr().is_terminal() && !no_color,
+        }
+    }
+}
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl EventSink for Terminal {
+    fn publish(&mut self, event: &Event) {
+        let Event::Log { source, line } = event else {
+            return;
+        };
+
+        // NOTE: Failing to write (ex: a closed pipe) isn't worth
+        // bringing down the watcher, so the result is ignored.
+        let _ = match source {
+            LogSource::Stdout if self.stdout_is_terminal => writeln!(std::io::stdout(), "{line}"),
+            LogSource::Stdout => writeln!(std::io::stdout(), "{}", ansi::strip(line)),
+            _ if self.stderr_is_terminal => writeln!(std::io::stderr(), "{line}"),
+            _ => writeln!(std::io::stderr(), "{}", ansi::strip(line)),
+        };
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    ansi,
+    build_options::BuildOptions,
+    event::{TestFailure, TestOutcome},
+};
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    process::{Command, Stdio},
+    sync::LazyLock,
+};
+
+/// A test result printed by libtest (ex: `test tests::parses ... ok`).
+static LIBTEST_RESULT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^test (.+?) \.\.\. (ok|FAILED|ignored)").expect("valid regex"));
+
+/// The header of the output libtest captured from a failed
+/// test (ex: `---- tests::parses stdout ----`).
+static LIBTEST_OUTPUT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^---- (.+?) std(out|err) ----$").expect("valid regex"));
+
+/// A test result printed by nextest (ex: `PASS [ 0.004s] my-crate tests::parses`).
+///
+/// NOTE: Skipped tests don't have a duration (ex: `SKIP [         ] ...`).
+static NEXTEST_RESULT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(PASS|FAIL|SKIP|SIGSEGV|SIGABRT|SIGBUS|SIGILL|ABORT|TIMEOUT|LEAK-FAIL)\s+\[[\s\d.s]*\]\s+(?:\(\s*\d+/\d+\)\s+)?\S+\s+(.+)$",
+    )
+    .expect("valid regex")
+});
+
+/// What runs the tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRunner {
+    /// `cargo test`, running the tests with libtest.
+    Cargo,
+
+    /// `cargo nextest run`, running every test in it's own process.
+    Nextest,
+}
+impl TestRunner {
+    /// Use nextest when it's installed, otherwise `cargo test`.
+    pub fn detect() -> Self {
+        let nextest = Command::new("cargo")
+            .args(["nextest", "--version"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+
+        match nextest {
+            true => Self::Nextest,
+            false => Self::Cargo,
+        }
+    }
+
+    /// Get the command running the tests (ex: `cargo test`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestRunner::Cargo => "cargo test",
+            TestRunner::Nextest => "cargo nextest",
+        }
+    }
+
+    /// Get the arguments to cargo running the tests with the build options,
+    /// only the ones named in `filters` (by their full path) when there are some.
+    ///
+    /// NOTE: Compiler messages are requested as JSON (like builds),
+    /// so diagnostics make it to the diagnostics panel, but nextest
+    /// doesn't support it and prints them as text.
+    pub fn args(&self, options: &BuildOptions, filters: &[String]) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            TestRunner::Cargo => [
+                "test",
+                "--no-fail-fast",
+                "--message-format=json-diagnostic-rendered-ansi",
+            ]
+            .map(String::from)
+            .to_vec(),
+            TestRunner::Nextest => [
+                "nextest",
+                "run",
+                "--no-fail-fast",
+                "--status-level=all",
+                "--final-status-level=none",
+                "--failure-output=immediate",
+            ]
+            .map(String::from)
+            .to_vec(),
+        };
+        args.extend(options.args(false));
+
+        match self {
+            TestRunner::Cargo => {
+                args.extend(["--", "--color=always"].map(String::from));
+                if !filters.is_empty() {
+                    args.push("--exact".to_string());
+                }
+            }
+            TestRunner::Nextest => {
+                args.push("--color=always".to_string());
+                if !filters.is_empty() {
+                    args.extend(["--", "--exact"].map(String::from));
+                }
+            }
+        }
+        args.extend(filters.iter().cloned());
+
+        args
+    }
+}
+
+/// The results of a test run, parsed line by line from the
+/// (libtest or nextest) output of the test runner.
+#[derive(Debug, Default)]
+pub struct TestRun {
+    /// The outcome of every test ran, in the order they finished.
+    results: Vec<(String, TestOutcome)>,
+
+    /// The output captured from each failed test.
+    output: HashMap<String, Vec<String>>,
+
+    /// The test the following lines are the captured output of, if any.
+    capturing: Option<String>,
+
+    /// Is the output from nextest ?
+    ///
+    /// NOTE: Nextest runs each test with libtest and prints it's output
+    /// when it fails, which isn't parsed as results of their own.
+    nextest: bool,
+}
+impl TestRun {
+    /// Parse a line of output, returning the result of a test if it's one.
+    pub fn parse_line(&mut self, line: &str) -> Option<(String, TestOutcome)> {
+        let line = ansi::strip(line);
+        let line = line.trim_end();
+
+        if let Some(captures) = NEXTEST_RESULT.captures(line) {
+            let outcome = match &captures[1] {
+                "PASS" => TestOutcome::Passed,
+                "SKIP" => TestOutcome::Ignored,
+                _ => TestOutcome::Failed,
+            };
+            let name = captures[2].to_string();
+
+            // NOTE: Nextest prints the output of a failed test right after it.
+            self.nextest = true;
+            self.capturing = (outcome == TestOutcome::Failed).then(|| name.clone());
+            return Some(self.record(name, outcome));
+        }
+
+        if !self.nextest
+            && let Some(captures) = LIBTEST_RESULT.captures(line)
+        {
+            let outcome = match &captures[2] {
+                "ok" => TestOutcome::Passed,
+                "ignored" => TestOutcome::Ignored,
+                _ => TestOutcome::Failed,
+            };
+            return Some(self.record(captures[1].to_string(), outcome));
+        }
+
+        if !self.nextest
+            && let Some(captures) = LIBTEST_OUTPUT.captures(line)
+        {
+            self.capturing = Some(captures[1].to_string());
+            return None;
+        }
+
+        // NOTE: Libtest lists the names of the failed tests after
+        // their output, and nextest a summary, ending the output.
+        if (!self.nextest && line == "failures:")
+            || line.trim_start().starts_with("Summary [")
+            || line.trim_start().starts_with("Cancelling")
+        {
+            self.capturing = None;
+            return None;
+        }
+
+        if let Some(name) = &self.capturing {
+            self.output
+                .entry(name.clone())
+                .or_default()
+                .push(line.to_string());
+        }
+
+        None
+    }
+
+    /// Record the outcome of a test.
+    fn record(&mut self, name: String, outcome: TestOutcome) -> (String, TestOutcome) {
+        self.results.push((name.clone(), outcome));
+        (name, outcome)
+    }
+
+    /// Did any test run ?
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Count the tests ran with an outcome.
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.results.iter().filter(|(_, o)| *o == outcome).count()
+    }
+
+    /// Get the failed tests, with their captured output.
+    pub fn failures(&self) -> Vec<TestFailure> {
+        self.results
+            .iter()
+            .filter(|(_, outcome)| *outcome == TestOutcome::Failed)
+            .map(|(name, _)| {
+                let mut output = self.output.get(name).cloned().unwrap_or_default();
+                while output.last().is_some_and(|line| line.trim().is_empty()) {
+                    output.pop();
+                }
+
+                TestFailure {
+                    name: name.clone(),
+                    output,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed the output of a test run to the parser, line by line.
+    fn parse(output: &str) -> TestRun {
+        let mut run = TestRun::default();
+        for line in output.lines() {
+            run.parse_line(line);
+        }
+
+        run
+    }
+
+    const LIBTEST_OUTPUT: &str = "
+running 3 tests
+test tests::adds ... ok
+test tests::fails ... \x1b[31mFAILED\x1b[0m
+test tests::slow ... ignored
+
+failures:
+
+---- tests::fails stdout ----
+some debug output
+
+thread 'tests::fails' panicked at src/lib.rs:9:49:
+assertion `left == right` failed
+  left: 3
+ right: 4
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::fails
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.02s
+";
+
+    const NEXTEST_OUTPUT: &str = "
+    Starting 2 tests across 1 binary (1 test skipped)
+        PASS [   0.003s] test-sample tests::adds
+        \x1b[31mFAIL\x1b[0m [   0.004s] test-sample tests::fails
+
+--- STDOUT:              test-sample tests::fails ---
+
+running 1 test
+test tests::fails ... FAILED
+
+failures:
+
+failures:
+    tests::fails
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 2 filtered out; finished in 0.00s
+
+
+--- STDERR:              test-sample tests::fails ---
+thread 'tests::fails' panicked at src/lib.rs:9:49:
+assertion `left == right` failed
+
+        SKIP [         ] test-sample tests::slow
+------------
+     Summary [   0.004s] 2 tests run: 1 passed, 1 failed, 1 skipped
+";
+
+    #[test]
+    fn only_runs_the_exact_filtered_tests() {
+        let options = BuildOptions::default();
+        let filters = [String::from("tests::fails")];
+
+        let args = TestRunner::Cargo.args(&options, &filters);
+        assert!(args.ends_with(&["--exact", "tests::fails"].map(String::from)));
+        let args = TestRunner::Nextest.args(&options, &filters);
+        assert!(args.ends_with(&["--", "--exact", "tests::fails"].map(String::from)));
+
+        let args = TestRunner::Nextest.args(&options, &[]);
+        assert!(!args.contains(&String::from("--exact")));
+    }
+
+    #[test]
+    fn parses_libtest_output() {
+        let run = parse(LIBTEST_OUTPUT);
+
+        assert_eq!(run.count(TestOutcome::Passed), 1);
+        assert_eq!(run.count(TestOutcome::Failed), 1);
+        assert_eq!(run.count(TestOutcome::Ignored), 1);
+
+        let failures = run.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "tests::fails");
+        assert_eq!(failures[0].output.first().unwrap(), "some debug output");
+        assert_eq!(
+            failures[0].output.last().unwrap(),
+            "note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace"
+        );
+    }
+
+    #[test]
+    fn parses_nextest_output() {
+        let run = parse(NEXTEST_OUTPUT);
+
+        assert_eq!(run.count(TestOutcome::Passed), 1);
+        assert_eq!(run.count(TestOutcome::Failed), 1);
+        assert_eq!(run.count(TestOutcome::Ignored), 1);
+
+        let failures = run.failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "tests::fails");
+        assert!(
+            failures[0]
+                .output
+                .contains(&String::from("test tests::fails ... FAILED"))
+        );
+        assert_eq!(
+            failures[0].output.last().unwrap(),
+            "assertion `left == right` failed"
+        );
+    }
+
+    #[test]
+    fn cancelling_ends_the_captured_output() {
+        let run = parse(
+            "        FAIL [   0.004s] test-sample tests::fails
+assertion failed
+   Cancelling due to interrupt: 1 test still running
+        PASS [   0.010s] test-sample tests::adds",
+        );
+
+        assert_eq!(run.count(TestOutcome::Passed), 1);
+        assert_eq!(run.failures()[0].output, ["assertion failed"]);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{config::TriggerConfig, error::Error};
+use glob::{MatchOptions, Pattern};
+use std::{collections::BTreeSet, path::Path};
+
+/// What to do about a change, in the order they're done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TriggerAction {
+    /// Nothing besides logging the change.
+    Notify,
+
+    /// Build the web frontend (`src/web`).
+    BuildWeb,
+
+    /// Build and restart the app.
+    Rebuild,
+
+    /// Restart the app without building it.
+    Restart,
+}
+impl TriggerAction {
+    /// Get a trigger action by its name in the config (ex: `rebuild`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "notify" => Some(TriggerAction::Notify),
+            "build_web" => Some(TriggerAction::BuildWeb),
+            "rebuild" => Some(TriggerAction::Rebuild),
+            "restart" => Some(TriggerAction::Restart),
+            _ => None,
+        }
+    }
+}
+
+/// A glob matching changed paths (ex: `migrations/**`).
+#[derive(Debug, Clone)]
+pub struct PathGlob {
+    pattern: Pattern,
+
+    /// Is the pattern matched against file names instead of whole paths ?
+    ///
+    /// NOTE: Like a `.gitignore`, a pattern without a `/`
+    /// (ex: `*.proto`) matches files in any directory.
+    file_name_only: bool,
+}
+impl PathGlob {
+    /// Parse a glob, failing with an invalid config error.
+    pub fn new(glob: &str) -> Result<Self, Error> {
+        let pattern = Pattern::new(glob)
+            .map_err(|e| Error::InvalidConfig(format!("invalid glob `{glob}`: {e}")))?;
+
+        Ok(Self {
+            pattern,
+            file_name_only: !glob.contains('/'),
+        })
+    }
+
+    /// Does a changed path (ex: `./src/main.rs`) match the glob ?
+    pub fn matches(&self, path: &str) -> bool {
+        let path = Path::new(path.strip_prefix("./").unwrap_or(path));
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+
+        match self.file_name_only {
+            true => path
+                .file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options)),
+            false => self.pattern.matches_path_with(path, options),
+        }
+    }
+}
+
+/// Changed paths matching a glob, and what to do about them.
+#[derive(Debug, Clone)]
+pub struct TriggerRule {
+    /// The paths this rule applies to.
+    glob: PathGlob,
+
+    /// A shell command to run (ex: code generation, migrations).
+    run: Option<String>,
+
+    /// What else to do.
+    actions: Vec<TriggerAction>,
+}
+impl TriggerRule {
+    /// Create a rule for paths matching a glob.
+    pub fn new(
+        glob: &str,
+        run: Option<String>,
+        actions: Vec<TriggerAction>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            glob: PathGlob::new(glob)?,
+            run,
+            actions,
+        })
+    }
+
+    /// Does this rule apply to a changed path (ex: `./src/main.rs`) ?
+    pub fn matches(&self, path: &str) -> bool {
+        self.glob.matches(path)
+    }
+}
+
+/// The rules deciding what to do about changes, the
+/// first rule matching a changed path applies to it.
+#[derive(Debug, Clone)]
+pub struct TriggerRules {
+    rules: Vec<TriggerRule>,
+}
+impl Default for TriggerRules {
+    fn default() -> Self {
+        Self {
+            rules: Self::default_rules(),
+        }
+    }
+}
+impl TriggerRules {
+    /// Create the rules described by the `[[triggers]]` of the config,
+    /// followed by the default rules for any path they don't match.
+    pub fn from_config(config: &[TriggerConfig]) -> Result<Self, Error> {
+        let mut rules = Vec::new();
+        for trigger in config {
+            let actions = trigger
+                .actions
+                .iter()
+                .map(|name| {
+                    TriggerAction::from_name(name).ok_or_else(|| {
+                        Error::InvalidConfig(format!(
+                            "unknown trigger action `{name}`, expected notify, build_web, rebuild or restart"
+                        ))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            rules.push(TriggerRule::new(
+                &trigger.glob,
+                trigger.run.clone(),
+                actions,
+            )?);
+        }
+        rules.extend(Self::default_rules());
+
+        Ok(Self { rules })
+    }
+
+    /// The default rules, building the web frontend along with the
+    /// app when it changes, and rebuilding the app for anything else.
+    fn default_rules() -> Vec<TriggerRule> {
+        [
+            (
+                "src/web/**",
+                vec![TriggerAction::BuildWeb, TriggerAction::Rebuild],
+            ),
+            ("**", vec![TriggerAction::Rebuild]),
+        ]
+        .into_iter()
+        .filter_map(|(glob, actions)| TriggerRule::new(glob, None, actions).ok())
+        .collect()
+    }
+
+    /// Decide what to do about a set of changed paths, the
+    /// union of what the rule matching each path says.
+    pub fn plan(&self, paths: &[String]) -> TriggerPlan {
+        let mut plan = TriggerPlan::default();
+        for path in paths {
+            let Some(rule) = self.rules.iter().find(|rule| rule.matches(path)) else {
+                continue;
+            };
+
+            if let Some(command) = &rule.run
+                && !plan.commands.contains(command)
+            {
+                plan.commands.push(command.clone());
+            }
+            plan.actions.extend(rule.actions.iter().copied());
+        }
+
+        plan
+    }
+}
+
+/// What to do about a set of changes.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerPlan {
+    /// The shell commands to run first, in the order of the rules.
+    pub commands: Vec<String>,
+
+    /// What to do after, in the order of `TriggerAction`.
+    pub actions: BTreeSet<TriggerAction>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    fn trigger(glob: &str, run: Option<&str>, actions: &[&str]) -> TriggerConfig {
+        TriggerConfig {
+            glob: glob.to_string(),
+            run: run.map(String::from),
+            actions: actions.iter().map(|action| action.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn globs_without_a_slash_match_file_names_in_any_directory() {
+        let glob = PathGlob::new("*.proto").unwrap();
+
+        assert!(glob.matches("./a/b/x.proto"));
+        assert!(glob.matches("x.proto"));
+        assert!(!glob.matches("./a/b/x.proto.bak"));
+    }
+
+    #[test]
+    fn globs_with_a_slash_match_whole_paths() {
+        let glob = PathGlob::new("migrations/**").unwrap();
+
+        assert!(glob.matches("./migrations/0001_init.sql"));
+        assert!(glob.matches("migrations/nested/0002.sql"));
+        assert!(!glob.matches("./db/migrations/0001_init.sql"));
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let rules = TriggerRules::from_config(&[trigger("*.md", None, &["notify"])]).unwrap();
+
+        let plan = rules.plan(&paths(&["./docs/guide.md"]));
+        assert_eq!(plan.actions, BTreeSet::from([TriggerAction::Notify]));
+
+        let plan = rules.plan(&paths(&["./src/main.rs"]));
+        assert_eq!(plan.actions, BTreeSet::from([TriggerAction::Rebuild]));
+    }
+
+    #[test]
+    fn the_actions_of_every_path_are_merged() {
+        let rules = TriggerRules::from_config(&[
+            trigger("*.proto", Some("make proto"), &["rebuild"]),
+            trigger("migrations/**", Some("make migrate"), &["restart"]),
+        ])
+        .unwrap();
+
+        let plan = rules.plan(&paths(&[
+            "./proto/a.proto",
+            "./proto/b.proto",
+            "./migrations/0001_init.sql",
+            "./src/web/index.html",
+        ]));
+        assert_eq!(plan.commands, ["make proto", "make migrate"]);
+        assert_eq!(
+            plan.actions,
+            BTreeSet::from([
+                TriggerAction::BuildWeb,
+                TriggerAction::Rebuild,
+                TriggerAction::Restart,
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_actions_are_rejected() {
+        let rules = TriggerRules::from_config(&[trigger("*.md", None, &["deploy"])]);
+
+        assert!(matches!(rules, Err(Error::InvalidConfig(_))));
+    }
+}
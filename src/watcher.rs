@@ -1,14 +1,79 @@
-use crate::{error::Error, interface::Display};
+use crate::{
+    build_options::BuildOptions,
+    bus::EventBus,
+    cargo,
+    error::Error,
+    event::{BuildKind, Diagnostic, Event, LogSource, TestOutcome},
+    hooks::{Hook, Hooks},
+    mode::Mode,
+    process::{APP, Output, Process, Processes},
+    ready::{ReadinessCheck, ReadinessProbe},
+    restart::{self, Restart, RestartPolicy},
+    supervisor::Procfile,
+    testing::{TestRun, TestRunner},
+    triggers::{TriggerAction, TriggerPlan, TriggerRules},
+    workspace::Workspace,
+};
 use chrono::{DateTime, SecondsFormat, Utc};
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     io::{BufRead, BufReader},
-    process::{Child, Command},
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
     thread,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
+/// The escape code erasing the rest of a line in a terminal (ex: redrawing a progress bar).
+const ERASE_LINE: &str = "\x1b[K";
+
+/// How often to check if the app exited, when it's restarted on exit.
+const RESTART_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How a `Watcher` keeps track of a supervised process (the app, or one of the `Procfile`).
+#[derive(Debug, Default)]
+struct Supervision {
+    /// The PID of the process while it's running, and when it was started.
+    running: Option<(u32, Instant)>,
+
+    /// How many times in a row the process was restarted.
+    restarts: u32,
+
+    /// When to restart the process, if it's waiting to be restarted.
+    pending_restart: Option<Instant>,
+}
+
+/// A command sent to a running `Watcher` (ex: from the interface).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatcherCommand {
+    /// Rebuild (and restart) the codebase, even without changes.
+    Rebuild,
+
+    /// Run a shell command (ex: `cargo test`), logging its output.
+    Exec(String),
+
+    /// Change how fast (in seconds) to check files for updates.
+    SetWatchInterval(u8),
+
+    /// Resize the pseudo-terminal the app runs in (ex: the pane showing it).
+    Resize { columns: u16, rows: u16 },
+
+    /// Run the tests, even without changes, only the ones
+    /// which failed the last time they ran if `only_failed`.
+    RunTests { only_failed: bool },
+
+    /// Switch to another mode (ex: only linting the codebase), building
+    /// the codebase right away the way the new mode does.
+    SetMode(Mode),
+
+    /// Change the options (profile, features, target) the next builds are ran with.
+    SetBuildOptions(BuildOptions),
+}
+
 /// Reponsible for watching the project for updates
 pub struct Watcher {
     /// How fast (in seconds) to check files for updates
@@ -17,16 +82,102 @@ pub struct Watcher {
     /// A vector of filenames to ignore
     ignore_list: Vec<String>,
 
+    /// The (absolute) paths of files to ignore, wherever they're named.
+    ignored_paths: Vec<PathBuf>,
+
     /// Target files to watch for changes
     targets: HashMap<String, SystemTime>,
 
-    /// Currently running build process
-    current_build_process: Arc<Mutex<Option<Child>>>,
+    /// The running processes, the app and the processes of the `Procfile`.
+    processes: Arc<Mutex<Processes>>,
+
+    /// Where events are published for subscribers (ex: the UI)
+    events: EventBus,
+
+    /// Should the stdin of the app be piped (see `WatcherBuilder::set_pipe_stdin`) ?
+    pipe_stdin: bool,
+
+    /// Should the app run inside a pseudo-terminal (see `WatcherBuilder::set_pty`) ?
+    pty: bool,
+
+    /// The size (columns, rows) of the pseudo-terminal the app runs in.
+    pty_size: (u16, u16),
+
+    /// Where commands are received while running.
+    commands: Receiver<WatcherCommand>,
+
+    /// Handed out to whoever wants to send commands (see `commands`).
+    command_sender: Sender<WatcherCommand>,
+
+    /// When and how to restart processes after they exit on their own.
+    restart_policy: RestartPolicy,
 
-    /// The list of log messages to display within the UI
-    display: Arc<Mutex<Display>>,
+    /// The last executable built successfully, ran again on restarts.
+    executable: Option<PathBuf>,
+
+    /// The processes supervised along with the app.
+    procfile: Procfile,
+
+    /// How each supervised process is doing, by name.
+    supervision: HashMap<String, Supervision>,
+
+    /// How to tell when the app is ready, if it's not as soon as it starts.
+    readiness_probe: Option<ReadinessProbe>,
+
+    /// When the current cycle (ex: a change being built and ran) started.
+    ///
+    /// NOTE: It's set where the cycle is triggered (ex: a change is
+    /// detected), not when building, so the readiness time covers it all.
+    cycle_started_at: Instant,
+
+    /// The shell commands to run around building and running the app.
+    hooks: Arc<Hooks>,
+
+    /// What to do about changes to specific paths.
+    triggers: TriggerRules,
+
+    /// The members of the cargo workspace, if there's more than one.
+    workspace: Option<Workspace>,
+
+    /// The workspace member the app belongs to, once it's been built.
+    app_package: Option<String>,
+
+    /// What to do with the codebase when it changes.
+    mode: Mode,
+
+    /// The arguments passed to clippy after `--` (ex: lint levels), in clippy mode.
+    clippy_args: Vec<String>,
+
+    /// The options (profile, features, target) builds are ran with.
+    build_options: BuildOptions,
+
+    /// What runs the tests, detected the first time they're ran.
+    test_runner: Option<TestRunner>,
+
+    /// The full path of every test which failed the last time the tests ran.
+    failed_tests: Vec<String>,
+
+    /// Where the name, PID and exit code of processes are received when they exit on their own.
+    exits: Receiver<(String, u32, Option<i32>)>,
+
+    /// Handed out to `monitor_process` (see `exits`).
+    exit_sender: Sender<(String, u32, Option<i32>)>,
 }
 impl Watcher {
+    /// Subscribe to the events published by this `Watcher` (log
+    /// messages, changes, builds, diagnostics, etc).
+    ///
+    /// NOTE: Subscribe before calling `start` to not miss any events.
+    pub fn subscribe(&self) -> std::sync::mpsc::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    /// Get a sender of commands to this `Watcher` while it's
+    /// running (ex: rebuilding on demand from the interface).
+    pub fn commands(&self) -> Sender<WatcherCommand> {
+        self.command_sender.clone()
+    }
+
     /// Start watching the project for updates
     pub fn start(&mut self) -> Result<(), Error> {
         // Initial state of targets
@@ -34,35 +185,70 @@ impl Watcher {
             self.log("failed to get initial state of target files");
         })?;
 
-        // Run an intial build on start up
+        self.load_workspace();
+
+        // NOTE: The other processes don't wait on the app to be built.
+        let names: Vec<String> = self
+            .procfile
+            .processes()
+            .iter()
+            .map(|process| process.name.clone())
+            .collect();
+        for name in names {
+            // NOTE: Failures are already logged.
+            let _ = self.try_run_process(&name);
+        }
+
+        self.events.publish(Event::ModeChanged { mode: self.mode });
+
+        // Run an initial build on start up
         //
         // TODO: This should also detect or have a config
         // option for doing an initial web build as well.
         self.log("running the initial build");
+        self.cycle_started_at = Instant::now();
         self.try_build_codebase(false)?;
 
         loop {
-            std::thread::sleep(Duration::from_secs(self.watch_interval as u64));
+            self.wait_for_commands(Duration::from_secs(self.watch_interval as u64));
 
             // Current state of targets
             let targets_current_state = self.try_get_targets().inspect_err(|_| {
                 self.log("failed to get current state of target files");
             })?;
 
-            'targets_loop: for (target, target_modified_ts) in &targets_current_state {
-                if self
-                    .targets
-                    .get(target)
-                    .is_some_and(|target| target != target_modified_ts)
-                {
-                    self.log(&format!("update detected @ {target}"));
+            let mut changed_targets: Vec<String> = targets_current_state
+                .iter()
+                .filter(|(target, target_modified_ts)| {
+                    self.targets
+                        .get(*target)
+                        .is_some_and(|target| target != *target_modified_ts)
+                })
+                .map(|(target, _)| target.clone())
+                .collect();
 
-                    let need_to_build_web: bool = target.contains("/src/web/");
-                    match self.try_build_codebase(need_to_build_web) {
-                        Ok(_) => break 'targets_loop,
-                        Err(_) => continue 'targets_loop,
-                    }
+            if !changed_targets.is_empty() {
+                // NOTE: The readiness time starts here, before the `on_change` hook.
+                self.cycle_started_at = Instant::now();
+                changed_targets.sort();
+                for target in &changed_targets {
+                    self.log(&format!("update detected @ {target}"));
                 }
+
+                let plan = self.triggers.plan(&changed_targets);
+                Self::run_hook(
+                    &self.events,
+                    &self.hooks,
+                    Hook::OnChange,
+                    &[("AMMO_CHANGED_PATHS", changed_targets.join("\n"))],
+                );
+                self.events.publish(Event::ChangeDetected {
+                    paths: changed_targets.clone(),
+                });
+
+                // NOTE: Failures are already logged, and we want to
+                // keep watching so the next change can fix the build.
+                let _ = self.handle_changes(plan, &changed_targets);
             }
 
             // Update initial state of targets to current state
@@ -70,6 +256,410 @@ impl Watcher {
         }
     }
 
+    /// Do what the trigger rules say about a set of changes, running their
+    /// commands first, then restarting the processes of the `Procfile`
+    /// watching those changes, and building and/or restarting the app.
+    fn handle_changes(&mut self, plan: TriggerPlan, paths: &[String]) -> Result<(), Error> {
+        for command in &plan.commands {
+            self.log(&format!("running `{command}`"));
+
+            let failure = match Self::run_shell(command, &[], &self.events) {
+                Ok(status) if status.success() => continue,
+                Ok(status) => match status.code() {
+                    Some(code) => format!("exited with code {code}"),
+                    None => String::from("was killed"),
+                },
+                Err(e) => e.to_string(),
+            };
+            self.log(&format!("skipping the rest, since `{command}` {failure}"));
+            return Ok(());
+        }
+
+        let restarted: Vec<String> = self
+            .procfile
+            .processes()
+            .iter()
+            .filter(|process| process.is_restarted_by(paths))
+            .map(|process| process.name.clone())
+            .collect();
+        for name in &restarted {
+            // NOTE: Failures are already logged.
+            let _ = self.restart_process(name);
+        }
+
+        let build_web = plan.actions.contains(&TriggerAction::BuildWeb);
+        let rebuild = plan.actions.contains(&TriggerAction::Rebuild);
+        // NOTE: Only run mode is about the app alone, the tests
+        // and checks cover the other members too.
+        if rebuild && (self.mode != Mode::Run || self.is_app_affected(paths)) {
+            return self.try_build_codebase(build_web);
+        }
+        if build_web {
+            self.try_build_web()?;
+        }
+        if plan.actions.contains(&TriggerAction::Restart) && self.mode == Mode::Run {
+            return self.restart_process(APP);
+        }
+        if !rebuild && !build_web && plan.commands.is_empty() && restarted.is_empty() {
+            self.log("nothing to rebuild for these changes");
+        }
+
+        Ok(())
+    }
+
+    /// Is the app affected by changes to a set of paths ? In a workspace,
+    /// only changes to the member it belongs to (or one it depends on) are.
+    fn is_app_affected(&mut self, paths: &[String]) -> bool {
+        // NOTE: Members could've been added, removed or started depending on each other.
+        if paths.iter().any(|path| path.ends_with("Cargo.toml")) {
+            self.load_workspace();
+        }
+
+        let (Some(workspace), Some(app_package)) = (&self.workspace, &self.app_package) else {
+            return true;
+        };
+        let Some(affected) = workspace.affected_by(paths) else {
+            return true;
+        };
+        if affected.contains(app_package) {
+            return true;
+        }
+
+        let changed = paths
+            .iter()
+            .filter_map(|path| workspace.member_of(Path::new(path)))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|member| format!("`{member}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.log(&format!(
+            "`{app_package}` doesn't depend on {changed}, not rebuilding"
+        ));
+
+        false
+    }
+
+    /// Read the members of the cargo workspace, to only rebuild the app
+    /// on changes to the members it depends on (see `is_app_affected`).
+    fn load_workspace(&mut self) {
+        self.workspace = Workspace::load()
+            .inspect_err(|_| {
+                self.log("failed to read the cargo workspace, rebuilding on any change")
+            })
+            .ok()
+            .filter(Workspace::has_many_members);
+    }
+
+    /// Wait until the next check for updates, handling any commands
+    /// received (and restarting the app if it exited) in the meantime.
+    fn wait_for_commands(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.handle_exits();
+
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return;
+            }
+
+            // NOTE: Wake up regularly to notice processes exiting.
+            let timeout = match self.restart_policy.restart {
+                Restart::Never => timeout,
+                _ => timeout.min(RESTART_CHECK_INTERVAL),
+            };
+            match self.commands.recv_timeout(timeout) {
+                Ok(command) => self.handle_command(command),
+                Err(RecvTimeoutError::Timeout) => {}
+                // NOTE: The watcher holds a sender, so this can't happen,
+                // but don't spin on it if it ever does.
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(timeout),
+            }
+        }
+    }
+
+    /// Restart processes that exited on their own, following
+    /// the restart policy, once they've been waiting long enough.
+    fn handle_exits(&mut self) {
+        while let Ok((name, pid, code)) = self.exits.try_recv() {
+            let supervision = self.supervision.entry(name.clone()).or_default();
+            let Some((_, started_at)) = supervision
+                .running
+                .take_if(|(running_pid, _)| *running_pid == pid)
+            else {
+                continue;
+            };
+            if !self.restart_policy.should_restart(code) {
+                continue;
+            }
+
+            // NOTE: A process that stayed up for a while isn't crash looping.
+            if started_at.elapsed() >= restart::STABLE_AFTER {
+                supervision.restarts = 0;
+            }
+            let restarts = supervision.restarts;
+            if restarts >= self.restart_policy.max_restarts {
+                let until = match name.as_str() {
+                    APP => "the next build",
+                    _ => "the next change restarting it",
+                };
+                self.log(&format!(
+                    "{} exited {} times in a row, it won't be restarted until {until}",
+                    Self::display_name(&name),
+                    restarts + 1
+                ));
+                self.events.publish(Event::CrashLoop { name, restarts });
+                continue;
+            }
+
+            let attempt = restarts + 1;
+            let delay = self.restart_policy.delay(attempt);
+            supervision.restarts = attempt;
+            supervision.pending_restart = Some(Instant::now() + delay);
+            self.log(&format!(
+                "restarting {} in {:.1}s ({attempt}/{})",
+                Self::display_name(&name),
+                delay.as_secs_f64(),
+                self.restart_policy.max_restarts
+            ));
+            self.events.publish(Event::RestartScheduled {
+                name,
+                attempt,
+                max_attempts: self.restart_policy.max_restarts,
+                delay_ms: delay.as_millis(),
+            });
+        }
+
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .supervision
+            .iter_mut()
+            .filter_map(|(name, supervision)| {
+                supervision
+                    .pending_restart
+                    .take_if(|restart_at| now >= *restart_at)
+                    .map(|_| name.clone())
+            })
+            .collect();
+        for name in due {
+            // NOTE: Failures are already logged.
+            self.cycle_started_at = now;
+            let _ = self.try_run_process(&name);
+        }
+    }
+
+    /// Handle a command sent to the watcher while it's running.
+    fn handle_command(&mut self, command: WatcherCommand) {
+        match command {
+            WatcherCommand::Rebuild => {
+                self.log("rebuild requested");
+                self.cycle_started_at = Instant::now();
+
+                // NOTE: Failures are already logged.
+                let _ = self.try_build_codebase(false);
+            }
+            WatcherCommand::Exec(command) => self.exec(command),
+            WatcherCommand::SetWatchInterval(seconds) => {
+                self.watch_interval = seconds;
+                self.log(&format!("watch interval set to {seconds}s"));
+            }
+            WatcherCommand::Resize { columns, rows } => {
+                self.pty_size = (columns, rows);
+                if let Ok(processes) = self.processes.lock() {
+                    for process in processes.iter() {
+                        let _ = process.resize(columns, rows).inspect_err(|e| {
+                            log::error!("Error resizing the pseudo-terminal: {e}")
+                        });
+                    }
+                }
+            }
+            WatcherCommand::RunTests { only_failed } => {
+                // NOTE: Failures are already logged.
+                let _ = self.try_run_tests(only_failed);
+            }
+            WatcherCommand::SetMode(mode) => self.set_mode(mode),
+            WatcherCommand::SetBuildOptions(build_options) => {
+                self.log(&format!(
+                    "build options set to {}, used from the next build on",
+                    build_options.summary()
+                ));
+                self.build_options = build_options;
+            }
+        }
+    }
+
+    /// Switch to another mode, stopping the app when the new mode
+    /// doesn't run it, and building the codebase the new way.
+    fn set_mode(&mut self, mode: Mode) {
+        if mode == self.mode {
+            self.log(&format!("already in {} mode", mode.name()));
+            return;
+        }
+
+        // NOTE: Only the run mode runs the app, the other processes are left running.
+        if mode != Mode::Run {
+            let _ = self.stop_process(APP);
+            self.supervision.remove(APP);
+        }
+
+        self.mode = mode;
+        self.log(&format!("switched to {} mode", mode.name()));
+        self.events.publish(Event::ModeChanged { mode });
+        self.cycle_started_at = Instant::now();
+
+        // NOTE: Failures are already logged.
+        let _ = self.try_build_codebase(false);
+    }
+
+    /// Run a shell command in the background, logging its output
+    /// along with the builds and reporting how it exited.
+    ///
+    /// NOTE: The command is kept with the supervised processes until
+    /// it exits, so it's killed along with them on exit (ex: a long
+    /// `cargo test`), leading it's own process group for the same
+    /// reasons they do (see `Process::spawn_piped`).
+    fn exec(&self, command: String) {
+        self.log(&format!("running `{command}`"));
+
+        let args = ["-c", command.as_str()];
+        let envs = [("CARGO_TERM_COLOR", "always")];
+        let (process, output) =
+            match Process::spawn_piped(Path::new("sh"), &args, &envs, true, self.pipe_stdin) {
+                Ok(spawned) => spawned,
+                Err(e) => {
+                    self.log(&format!("failed to run `{command}`: {e}"));
+                    return;
+                }
+            };
+        let pid = process.id();
+        if let Ok(mut processes) = self.processes.lock() {
+            processes.insert_command(process);
+        }
+
+        let (events, processes) = (self.events.clone(), Arc::clone(&self.processes));
+        thread::spawn(move || {
+            let outputs = match output {
+                Output::Piped { stdout, stderr } => vec![stdout, stderr],
+                Output::Pty(output) => vec![output],
+            };
+            let readers: Vec<_> = outputs
+                .into_iter()
+                .map(|output| Self::spawn_log_reader(output, events.clone()))
+                .collect();
+            for reader in readers {
+                let _ = reader.join();
+            }
+
+            // NOTE: The command isn't there anymore if it was killed (ex: on exit).
+            let process = processes
+                .lock()
+                .ok()
+                .and_then(|mut processes| processes.remove_command(pid));
+            let msg = match process.map(|mut process| process.wait()) {
+                Some(Ok(Some(code))) => format!("`{command}` exited with code {code}"),
+                Some(Ok(None)) | None => format!("`{command}` was killed"),
+                Some(Err(e)) => format!("failed to run `{command}`: {e}"),
+            };
+            events.publish(Event::Log {
+                source: LogSource::Watcher,
+                line: Self::format_log_msg(&msg),
+            });
+        });
+    }
+
+    /// Run a hook (if there is one) until it exits, logging it's output
+    /// along with the builds, returning false if it failed.
+    fn run_hook(events: &EventBus, hooks: &Hooks, hook: Hook, envs: &[(&str, String)]) -> bool {
+        let Some(command) = hooks.command(hook) else {
+            return true;
+        };
+        events.publish(Event::Log {
+            source: LogSource::Watcher,
+            line: Self::format_log_msg(&format!("running the `{}` hook", hook.name())),
+        });
+
+        let failure = match Self::run_shell(command, envs, events) {
+            Ok(status) if status.success() => return true,
+            Ok(status) => match status.code() {
+                Some(code) => format!("exited with code {code}"),
+                None => String::from("was killed"),
+            },
+            Err(e) => e.to_string(),
+        };
+        events.publish(Event::Log {
+            source: LogSource::Watcher,
+            line: Self::format_log_msg(&format!("the `{}` hook failed: {failure}", hook.name())),
+        });
+
+        false
+    }
+
+    /// Run a hook (if there is one) in the background.
+    fn spawn_hook(
+        events: &EventBus,
+        hooks: &Arc<Hooks>,
+        hook: Hook,
+        envs: Vec<(&'static str, String)>,
+    ) {
+        if hooks.command(hook).is_none() {
+            return;
+        }
+
+        let (events, hooks) = (events.clone(), Arc::clone(hooks));
+        thread::spawn(move || Self::run_hook(&events, &hooks, hook, &envs));
+    }
+
+    /// Run a shell command until it exits, logging it's output along with the builds.
+    fn run_shell(
+        command: &str,
+        envs: &[(&str, String)],
+        events: &EventBus,
+    ) -> std::io::Result<std::process::ExitStatus> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("CARGO_TERM_COLOR", "always")
+            .envs(envs.iter().map(|(key, value)| (key, value)))
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()?;
+
+        let readers = [
+            child
+                .stdout
+                .take()
+                .map(|stdout| Self::spawn_log_reader(stdout, events.clone())),
+            child
+                .stderr
+                .take()
+                .map(|stderr| Self::spawn_log_reader(stderr, events.clone())),
+        ];
+
+        let status = child.wait();
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+
+        status
+    }
+
+    /// Read the output of a command in the background,
+    /// publishing each line as a build log message.
+    fn spawn_log_reader(
+        output: impl std::io::Read + Send + 'static,
+        events: EventBus,
+    ) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines().map_while(Result::ok) {
+                events.publish(Event::Log {
+                    source: LogSource::Build,
+                    line,
+                });
+            }
+        })
+    }
+
     /// Try to get a hashmap of target names and their last modified time
     fn try_get_targets(&self) -> Result<HashMap<String, SystemTime>, Error> {
         let mut targets: HashMap<String, SystemTime> = HashMap::new();
@@ -98,7 +688,7 @@ impl Watcher {
                 })?;
                 let path = entry.path();
 
-                if self.is_valid_target(&filename) {
+                if self.is_valid_target(&filename, &path) {
                     if path.is_dir() && path.to_str().is_some() {
                         // SAFETY: This unwrap is safe via the invariant check above
                         self.walk_codebase(path.to_str().unwrap(), targets)
@@ -123,9 +713,16 @@ impl Watcher {
         Ok(())
     }
 
-    /// Check if a target is valid (not in the ignore list)
-    fn is_valid_target(&self, filename: &str) -> bool {
-        !self.ignore_list.contains(&filename.to_string())
+    /// Check if a target is valid (not in the ignore list, nor an ignored path)
+    fn is_valid_target(&self, filename: &str, path: &Path) -> bool {
+        if self.ignore_list.contains(&filename.to_string()) {
+            return false;
+        }
+        if self.ignored_paths.is_empty() {
+            return true;
+        }
+
+        std::path::absolute(path).is_ok_and(|path| !self.ignored_paths.contains(&path))
     }
 
     /// Try to get a timestamp of a paths last modification
@@ -135,129 +732,702 @@ impl Watcher {
     }
 
     /// Handle building and running the codebase.
-    //
-    // TODO: This needs to be refactored and cleaned up.
+    ///
+    /// NOTE: The running app is only replaced once the new code compiled,
+    /// so a compile error keeps the last good version running.
     pub fn try_build_codebase(&mut self, need_to_build_web: bool) -> Result<(), Error> {
-        // If there's already a build running then kill and reset it
-        if let Ok(mut current_build_process) = self.current_build_process.lock() {
-            if let Some(ref mut old_build) = current_build_process.as_mut() {
-                let pid = old_build.id();
-
-                old_build.kill().inspect_err(|_| {
-                    self.log(&format!(
-                        "failed to kill the previous (stale) running build: (PID: {pid})",
-                    ));
+        if !Self::run_hook(&self.events, &self.hooks, Hook::PreBuild, &[]) {
+            self.log("skipping the build, since the `pre_build` hook failed");
+            return Ok(());
+        }
+
+        if need_to_build_web {
+            self.try_build_web()?;
+        }
+
+        let (success, executable) = match self.mode {
+            Mode::Run => self.try_build_rust()?,
+            Mode::Test => (self.try_run_tests(false)?, None),
+            Mode::Check | Mode::Clippy => (self.try_check_rust()?, None),
+        };
+        Self::run_hook(
+            &self.events,
+            &self.hooks,
+            Hook::PostBuild,
+            &[("AMMO_BUILD_SUCCESS", success.to_string())],
+        );
+
+        let Some(executable) = executable else {
+            if !success && self.is_app_running() {
+                self.log("build failed, keeping the last good version running");
+            }
+            return Ok(());
+        };
+
+        // NOTE: New code gets a fresh start, whether or not the last one crashed.
+        self.stop_process(APP)?;
+        self.supervision.remove(APP);
+        self.executable = Some(executable);
+        self.try_run_process(APP)
+    }
+
+    /// Build the web frontend of the codebase (`src/web`).
+    fn try_build_web(&self) -> Result<(), Error> {
+        self.events.publish(Event::BuildStarted {
+            kind: BuildKind::Web,
+        });
+        let started_at = Instant::now();
+
+        // NOTE: No need to track this process, we implicitly wait for it's completion.
+        match Command::new("sh")
+            .arg("-c")
+            // TODO: The web build tool should be configurable, I've been using
+            // bun a lot more than npm personally and lot's of people use other
+            // stuff like yarn, pnpm, deno, etc
+            .arg("cd src/web && npm run build")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+        {
+            Ok(build_process) => {
+                let output = build_process.wait_with_output().inspect_err(|e| {
+                    self.log(&format!("failed to build web:\n {e}"));
                 })?;
+                let stdout_str = String::from_utf8_lossy(&output.stdout);
+                let stderr_str = String::from_utf8_lossy(&output.stderr);
+
+                // TODO: Figure out a fancy way to display the difference
+                // between logs originating from stdout and stderr.
+                for line in stdout_str.lines().chain(stderr_str.lines()) {
+                    self.events.publish(Event::Log {
+                        source: LogSource::Web,
+                        line: line.to_string(),
+                    });
+                }
 
-                *current_build_process = None;
+                self.events.publish(Event::BuildFinished {
+                    kind: BuildKind::Web,
+                    success: output.status.success(),
+                    duration_ms: started_at.elapsed().as_millis(),
+                });
+            }
+            Err(e) => {
+                self.log(&format!("failed to run web build command: {e}"));
+                return Err(Error::BuildFailed(e));
             }
         }
 
-        if need_to_build_web {
-            // NOTE: No need to track this process, we implicitly wait for it's completion.
-            match Command::new("sh")
-                .arg("-c")
-                // TODO: The web build tool should be configurable, I've been using
-                // bun a lot more than npm personally and lot's of people use other
-                // stuff like yarn, pnpm, deno, etc
-                .arg("cd src/web && npm run build")
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped())
-                .spawn()
-            {
-                Ok(build_process) => {
-                    let output = build_process.wait_with_output().inspect_err(|e| {
-                        self.log(&format!("failed to build web:\n {e}"));
-                    })?;
-                    let stdout_str = String::from_utf8_lossy(&output.stdout);
-                    let stderr_str = String::from_utf8_lossy(&output.stderr);
-
-                    // TODO: Figure out a fancy way to display the difference
-                    // between logs originating from stdout and stderr.
-                    self.log(&stdout_str);
-                    self.log(&stderr_str);
-                }
-                Err(e) => {
-                    self.log(&format!("failed to run web build command: {e}"));
-                    return Err(Error::BuildFailed(e));
+        Ok(())
+    }
+
+    /// Build the rust codebase with cargo, returning if it compiled,
+    /// and the path of the app's executable if one was built.
+    fn try_build_rust(&mut self) -> Result<(bool, Option<PathBuf>), Error> {
+        // NOTE: In a workspace, only the member of the app (and what it depends on) is built.
+        let mut build_command = Command::new("cargo");
+        build_command.args([
+            "build",
+            "--color=always",
+            "--message-format=json-diagnostic-rendered-ansi",
+        ]);
+        if self.workspace.is_some()
+            && let Some(app_package) = &self.app_package
+        {
+            build_command.args(["-p", app_package]);
+        }
+        build_command
+            .args(self.build_options.args(true))
+            .env("RUSTFLAGS", "-Awarnings");
+
+        let (success, artifacts) = self.run_cargo(build_command, BuildKind::Rust)?;
+        if !success {
+            return Ok((false, None));
+        }
+
+        // NOTE: In a workspace, prefer the binary of the package we're ran in.
+        let executables: Vec<&cargo::Artifact> = artifacts
+            .iter()
+            .filter(|artifact| artifact.is_bin() || artifact.is_example())
+            .collect();
+        let manifest_path = std::env::current_dir()?.join("Cargo.toml");
+        let artifact = executables
+            .iter()
+            .rfind(|artifact| artifact.manifest_path == manifest_path)
+            .or(executables.last());
+        if let Some(workspace) = &self.workspace
+            && let Some(artifact) = artifact
+        {
+            self.app_package = workspace
+                .member_of(&artifact.manifest_path)
+                .map(String::from);
+        }
+        let executable = artifact.and_then(|artifact| artifact.executable.clone());
+        if executable.is_none() {
+            self.log("nothing to run, no binary was built");
+        }
+
+        Ok((true, executable))
+    }
+
+    /// Check (or lint with clippy, in clippy mode) the rust codebase
+    /// without building anything to run, returning if it compiled.
+    ///
+    /// NOTE: Unlike builds, warnings aren't silenced, they're what this is for.
+    fn try_check_rust(&self) -> Result<bool, Error> {
+        let (subcommand, kind) = match self.mode {
+            Mode::Clippy => ("clippy", BuildKind::Clippy),
+            _ => ("check", BuildKind::Check),
+        };
+
+        let mut check_command = Command::new("cargo");
+        check_command.args([
+            subcommand,
+            "--color=always",
+            "--message-format=json-diagnostic-rendered-ansi",
+        ]);
+        if self.workspace.is_some() {
+            check_command.arg("--workspace");
+        }
+        check_command.args(self.build_options.args(false));
+        if self.mode == Mode::Clippy && !self.clippy_args.is_empty() {
+            check_command.arg("--").args(&self.clippy_args);
+        }
+
+        let (success, _) = self.run_cargo(check_command, kind)?;
+        Ok(success)
+    }
+
+    /// Run a cargo command building the codebase, publishing its diagnostics and
+    /// output as it goes, returning if it succeeded and the artifacts it built.
+    ///
+    /// NOTE: Cargo's JSON messages let us know what the diagnostics were,
+    /// what was built and when the build finished.
+    fn run_cargo(
+        &self,
+        mut command: Command,
+        kind: BuildKind,
+    ) -> Result<(bool, Vec<cargo::Artifact>), Error> {
+        self.events.publish(Event::BuildStarted { kind });
+        let started_at = Instant::now();
+
+        let mut build_process = command
+            .env("CARGO_TERM_COLOR", "always")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                self.log("failed to run rust build command");
+                Error::BuildFailed(e)
+            })?;
+
+        // SAFETY: These unwraps are safe since stdout and stderr were piped above
+        let stderr_reader =
+            Self::spawn_log_reader(build_process.stderr.take().unwrap(), self.events.clone());
+        let stdout = build_process.stdout.take().unwrap();
+
+        let mut artifacts = Vec::new();
+        let mut success = false;
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            match cargo::Message::parse(&line) {
+                Some(cargo::Message::CompilerDiagnostic { message }) => {
+                    self.publish_diagnostic(Diagnostic::from(message));
                 }
+                Some(cargo::Message::CompilerArtifact(artifact)) => artifacts.push(artifact),
+                Some(cargo::Message::BuildFinished { success: finished }) => success = finished,
+                Some(_) => {}
+                None => self.events.publish(Event::Log {
+                    source: LogSource::Build,
+                    line,
+                }),
             }
         }
 
-        // else build the rust codebase
-        match Command::new("sh")
-            .arg("-c")
-            .arg("RUSTFLAGS=\"-Awarnings\" cargo run --color=always")
-            .env("RUST_LOG_STYLE", "always")
-            .env("RUST_TERM_STYLE", "always")
+        let status = build_process.wait().map_err(Error::BuildFailed)?;
+        let _ = stderr_reader.join();
+        let success = success && status.success();
+        self.events.publish(Event::BuildFinished {
+            kind,
+            success,
+            duration_ms: started_at.elapsed().as_millis(),
+        });
+
+        Ok((success, artifacts))
+    }
+
+    /// Publish a diagnostic reported by the compiler, logging it along with the build.
+    fn publish_diagnostic(&self, diagnostic: Diagnostic) {
+        if let Some(rendered) = &diagnostic.rendered {
+            self.events.publish(Event::Log {
+                source: LogSource::Build,
+                line: rendered.trim_end().to_string(),
+            });
+        }
+        self.events.publish(Event::Diagnostic(diagnostic));
+    }
+
+    /// Build and run the tests, returning if they all passed, only the
+    /// ones which failed the last time they ran if `only_failed`.
+    ///
+    /// NOTE: The output of the test runner is parsed as it's logged,
+    /// publishing the result of each test as soon as it's known.
+    fn try_run_tests(&mut self, only_failed: bool) -> Result<bool, Error> {
+        let filters = match only_failed {
+            true if self.failed_tests.is_empty() => {
+                self.log("no failed tests to run again");
+                return Ok(true);
+            }
+            true => self.failed_tests.clone(),
+            false => Vec::new(),
+        };
+        let runner = *self.test_runner.get_or_insert_with(TestRunner::detect);
+        match only_failed {
+            true => self.log(&format!(
+                "running the failed tests again ({}) with `{}`",
+                filters.len(),
+                runner.name()
+            )),
+            false => self.log(&format!("running the tests with `{}`", runner.name())),
+        }
+
+        self.events.publish(Event::BuildStarted {
+            kind: BuildKind::Tests,
+        });
+        let started_at = Instant::now();
+
+        let mut test_process = Command::new("cargo")
+            .args(runner.args(&self.build_options, &filters))
+            .env("RUSTFLAGS", "-Awarnings")
             .env("CARGO_TERM_COLOR", "always")
+            .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .spawn()
-        {
-            Ok(mut build_process) => {
-                // Read stdout and display them as logs
-                let display = Arc::clone(&self.display);
-                if let Some(stdout) = build_process.stdout.take() {
-                    thread::spawn(move || {
-                        let reader = BufReader::new(stdout);
-                        for line in reader.lines() {
-                            match line {
-                                Ok(text) => {
-                                    if let Ok(mut display) = display.lock() {
-                                        display.add_log(text);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Error reading child stdout: {e}");
-                                    break;
-                                }
-                            }
-                        }
+            .map_err(|e| {
+                self.log("failed to run the test command");
+                Error::BuildFailed(e)
+            })?;
+
+        // NOTE: Libtest prints the results to stdout and nextest to
+        // stderr, so both are read (in the order they're printed).
+        let (line_sender, lines) = mpsc::channel();
+        // SAFETY: These unwraps are safe since stdout and stderr were piped above
+        let outputs: [Box<dyn std::io::Read + Send>; 2] = [
+            Box::new(test_process.stdout.take().unwrap()),
+            Box::new(test_process.stderr.take().unwrap()),
+        ];
+        for output in outputs {
+            let line_sender = line_sender.clone();
+            Self::spawn_line_reader(output, move |line| {
+                let _ = line_sender.send(line);
+            });
+        }
+        drop(line_sender);
+
+        let mut run = TestRun::default();
+        for line in lines {
+            match cargo::Message::parse(&line) {
+                Some(cargo::Message::CompilerDiagnostic { message }) => {
+                    self.publish_diagnostic(Diagnostic::from(message));
+                }
+                Some(_) => {}
+                None => {
+                    if let Some((name, outcome)) = run.parse_line(&line) {
+                        self.events.publish(Event::TestResult { name, outcome });
+                    }
+                    self.events.publish(Event::Log {
+                        source: LogSource::Build,
+                        line,
                     });
                 }
+            }
+        }
 
-                // Read stderr and display them as logs
-                let display = Arc::clone(&self.display);
-                if let Some(stderr) = build_process.stderr.take() {
-                    thread::spawn(move || {
-                        let reader = BufReader::new(stderr);
-                        for line in reader.lines() {
-                            match line {
-                                Ok(text) => {
-                                    if let Ok(mut display) = display.lock() {
-                                        display.add_log(text);
-                                    }
-                                }
-                                Err(e) => {
-                                    log::error!("Error reading child stderr: {e}");
-                                    break;
-                                }
-                            }
-                        }
-                    });
+        let status = test_process.wait().map_err(Error::BuildFailed)?;
+
+        let failures = run.failures();
+        let (passed, failed, ignored) = (
+            run.count(TestOutcome::Passed),
+            run.count(TestOutcome::Failed),
+            run.count(TestOutcome::Ignored),
+        );
+        // NOTE: When the tests didn't compile, the last failures are still worth running again.
+        if !run.is_empty() {
+            self.failed_tests = failures
+                .iter()
+                .map(|failure| failure.name.clone())
+                .collect();
+        }
+        if run.is_empty() && !status.success() {
+            self.log("the tests failed to compile");
+        } else {
+            self.log(&format!(
+                "tests finished: {passed} passed, {failed} failed, {ignored} ignored"
+            ));
+        }
+
+        self.events.publish(Event::TestsFinished {
+            passed,
+            failed,
+            ignored,
+            failures,
+        });
+        self.events.publish(Event::BuildFinished {
+            kind: BuildKind::Tests,
+            success: status.success(),
+            duration_ms: started_at.elapsed().as_millis(),
+        });
+
+        Ok(status.success())
+    }
+
+    /// Is a version of the app currently running ?
+    fn is_app_running(&self) -> bool {
+        self.processes
+            .lock()
+            .is_ok_and(|processes| processes.get(APP).is_some())
+    }
+
+    /// Get how a supervised process is called in logs (ex: `the app`, `` `worker` ``).
+    fn display_name(name: &str) -> String {
+        match name {
+            APP => String::from("the app"),
+            _ => format!("`{name}`"),
+        }
+    }
+
+    /// Stop a running process (if it's running).
+    fn stop_process(&mut self, name: &str) -> Result<(), Error> {
+        if let Some(supervision) = self.supervision.get_mut(name) {
+            supervision.running = None;
+        }
+
+        if let Ok(mut processes) = self.processes.lock()
+            && let Some(old_process) = processes.get_mut(name)
+        {
+            let pid = old_process.id();
+
+            old_process.kill().inspect_err(|_| {
+                self.log(&format!(
+                    "failed to kill the previous (stale) running version of {}: (PID: {pid})",
+                    Self::display_name(name)
+                ));
+            })?;
+            let code = old_process.wait().ok().flatten();
+            processes.remove(name);
+            drop(processes);
+            Self::publish_exit(&self.events, &self.hooks, name, pid, code);
+        }
+
+        Ok(())
+    }
+
+    /// Restart a supervised process, without building it, for
+    /// the app, the last version of it that was built.
+    fn restart_process(&mut self, name: &str) -> Result<(), Error> {
+        if name == APP && self.executable.is_none() {
+            self.log("nothing to restart, no binary was built yet");
+            return Ok(());
+        }
+
+        self.stop_process(name)?;
+        self.supervision.remove(name);
+        self.try_run_process(name)
+    }
+
+    /// Run a supervised process, the last executable built
+    /// for the app, or the command of a process of the `Procfile`.
+    fn try_run_process(&mut self, name: &str) -> Result<(), Error> {
+        let (program, args) = match self.procfile.get(name) {
+            Some(process) => {
+                self.log(&format!("running `{name}`: `{}`", process.command));
+                (
+                    PathBuf::from("sh"),
+                    vec![String::from("-c"), process.command.clone()],
+                )
+            }
+            None => {
+                let Some(executable) = self.executable.clone() else {
+                    return Ok(());
+                };
+                let relative = executable
+                    .strip_prefix(std::env::current_dir()?)
+                    .unwrap_or(&executable);
+                self.log(&format!("running `{}`", relative.display()));
+                (executable, Vec::new())
+            }
+        };
+
+        // NOTE: The lines of the app are sent to the readiness probe before
+        // it starts, to not miss the line saying it's ready.
+        let (probe_sender, log_lines) = match &self.readiness_probe {
+            Some(probe) if name == APP && matches!(probe.check, ReadinessCheck::Log(_)) => {
+                let (sender, receiver) = mpsc::channel();
+                (Some(sender), Some(receiver))
+            }
+            _ => (None, None),
+        };
+
+        // NOTE: Only the app can share our stdin, the other processes would
+        // fight over it. And processes only lead their own process group when
+        // the interface is there to kill them on exit (see `Process::spawn_piped`).
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let envs = [("RUST_LOG_STYLE", "always"), ("RUST_TERM_STYLE", "always")];
+        let pipe_stdin = self.pipe_stdin || name != APP;
+        let spawned = match self.pty {
+            true => Process::spawn_pty(&program, &args, &envs, self.pty_size),
+            false => Process::spawn_piped(&program, &args, &envs, pipe_stdin, self.pipe_stdin),
+        };
+        let (process, output) = spawned.map_err(|e| {
+            self.log(&format!("failed to run {}: {e}", Self::display_name(name)));
+            Error::BuildFailed(e)
+        })?;
+
+        // Read the output of the process and display them as logs
+        // NOTE: Stdout and stderr are one stream in a terminal,
+        // so all of the output of the process is seen as stdout.
+        let outputs = match output {
+            Output::Piped { stdout, stderr } => {
+                vec![(stdout, LogSource::Stdout), (stderr, LogSource::Stderr)]
+            }
+            Output::Pty(output) => vec![(output, LogSource::Stdout)],
+        };
+        let prefix = self.procfile.prefix(name).to_string();
+        for (output, source) in outputs {
+            let (events, prefix, probe_sender) =
+                (self.events.clone(), prefix.clone(), probe_sender.clone());
+            Self::spawn_line_reader(output, move |line| {
+                if let Some(probe_sender) = &probe_sender {
+                    let _ = probe_sender.send(line.clone());
                 }
+                events.publish(Event::Log {
+                    source,
+                    line: format!("{prefix}{line}"),
+                })
+            });
+        }
+
+        // Store this process in case we need to kill it later
+        let pid = process.id();
+        if let Ok(mut processes) = self.processes.lock() {
+            processes.insert(name, process);
+        }
+        self.supervision
+            .entry(name.to_string())
+            .or_default()
+            .running = Some((pid, Instant::now()));
+        self.events.publish(Event::ProcessStarted {
+            name: name.to_string(),
+            pid,
+        });
+
+        // Report when the process is ready (ex: accepting connections)
+        match self.readiness_probe.clone().filter(|_| name == APP) {
+            Some(probe) => Self::wait_until_ready(
+                pid,
+                self.cycle_started_at,
+                probe,
+                log_lines,
+                Arc::clone(&self.processes),
+                self.events.clone(),
+                Arc::clone(&self.hooks),
+            ),
+            None => {
+                Self::publish_ready(&self.events, &self.hooks, name, pid, self.cycle_started_at)
+            }
+        }
+
+        // Report when the process exits on it's own (ex: crashed)
+        Self::monitor_process(
+            name.to_string(),
+            pid,
+            Arc::clone(&self.processes),
+            self.events.clone(),
+            Arc::clone(&self.hooks),
+            self.exit_sender.clone(),
+        );
+
+        Ok(())
+    }
+
+    /// Check if the app is ready in the background, until it passes the
+    /// readiness probe, or stops running (ex: crashed, or was replaced).
+    fn wait_until_ready(
+        pid: u32,
+        started_at: Instant,
+        probe: ReadinessProbe,
+        log_lines: Option<Receiver<String>>,
+        processes: Arc<Mutex<Processes>>,
+        events: EventBus,
+        hooks: Arc<Hooks>,
+    ) {
+        thread::spawn(move || {
+            loop {
+                let ready = match &log_lines {
+                    Some(log_lines) => match log_lines.recv_timeout(probe.interval) {
+                        Ok(line) => probe.check.matches(&line),
+                        Err(RecvTimeoutError::Timeout) => false,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    },
+                    None => {
+                        thread::sleep(probe.interval);
+                        probe.check.is_ready()
+                    }
+                };
 
-                // Store this process in case we need to kill it later
-                if let Ok(mut current_build_process) = self.current_build_process.lock() {
-                    *current_build_process = Some(build_process);
+                let is_running = processes
+                    .lock()
+                    .is_ok_and(|processes| processes.is_running(APP, pid));
+                if !is_running {
+                    return;
                 }
+                if ready {
+                    Self::publish_ready(&events, &hooks, APP, pid, started_at);
+                    return;
+                }
+            }
+        });
+    }
 
-                Ok(())
+    /// Let everyone know a process is ready, and how long it took.
+    fn publish_ready(
+        events: &EventBus,
+        hooks: &Arc<Hooks>,
+        name: &str,
+        pid: u32,
+        started_at: Instant,
+    ) {
+        let duration = started_at.elapsed();
+        events.publish(Event::Log {
+            source: LogSource::Watcher,
+            line: Self::format_log_msg(&format!(
+                "{} is ready ({:.2}s)",
+                Self::display_name(name),
+                duration.as_secs_f64()
+            )),
+        });
+        events.publish(Event::Ready {
+            name: name.to_string(),
+            pid,
+            duration_ms: duration.as_millis(),
+        });
+        Self::spawn_hook(
+            events,
+            hooks,
+            Hook::OnReady,
+            vec![
+                ("AMMO_PROCESS", name.to_string()),
+                ("AMMO_PID", pid.to_string()),
+                ("AMMO_READY_MS", duration.as_millis().to_string()),
+            ],
+        );
+    }
+
+    /// Let everyone know a process exited, `code` is `None` when it was killed.
+    fn publish_exit(
+        events: &EventBus,
+        hooks: &Arc<Hooks>,
+        name: &str,
+        pid: u32,
+        code: Option<i32>,
+    ) {
+        events.publish(Event::ProcessExited {
+            name: name.to_string(),
+            pid,
+            code,
+        });
+        Self::spawn_hook(
+            events,
+            hooks,
+            Hook::OnExit,
+            vec![
+                ("AMMO_PROCESS", name.to_string()),
+                ("AMMO_PID", pid.to_string()),
+                (
+                    "AMMO_EXIT_CODE",
+                    code.map(|code| code.to_string()).unwrap_or_default(),
+                ),
+            ],
+        );
+    }
+
+    /// Read the output of a process line by line in the background.
+    ///
+    /// NOTE: Terminals end lines with `\r\n`, and redraw a line (ex: progress
+    /// bars) by going back to its start with `\r` and erasing it, so only
+    /// what's left visible on the line after the last `\r` is kept.
+    fn spawn_line_reader(
+        output: Box<dyn std::io::Read + Send>,
+        mut handle_line: impl FnMut(String) + Send + 'static,
+    ) {
+        thread::spawn(move || {
+            let mut reader = BufReader::new(output);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buf);
+                        let line = line.trim_end_matches(['\n', '\r']);
+                        let line = line.rsplit('\r').next().unwrap_or_default();
+                        let line = line.trim_start_matches(ERASE_LINE);
+                        handle_line(line.to_string());
+                    }
+                    // NOTE: Reading a terminal errors once the process exits.
+                    Err(e) => {
+                        log::error!("Error reading child output: {e}");
+                        break;
+                    }
+                }
             }
-            Err(e) => {
-                self.log("failed to run rust build command");
+        });
+    }
+
+    /// Watch a running process in the background, publishing an event (and
+    /// sending it's name and exit code to `exits`) if it exits on it's own.
+    ///
+    /// NOTE: Once the process is replaced (killed by a rebuild or on
+    /// shutdown) we stop watching, whoever killed it reports the exit.
+    fn monitor_process(
+        name: String,
+        pid: u32,
+        processes: Arc<Mutex<Processes>>,
+        events: EventBus,
+        hooks: Arc<Hooks>,
+        exits: Sender<(String, u32, Option<i32>)>,
+    ) {
+        thread::spawn(move || {
+            loop {
+                thread::sleep(Duration::from_millis(250));
+
+                let Ok(mut processes) = processes.lock() else {
+                    return;
+                };
+                let Some(process) = processes.get_mut(&name) else {
+                    return;
+                };
+                if process.id() != pid {
+                    return;
+                }
 
-                Err(Error::BuildFailed(e))
+                if let Ok(Some(code)) = process.try_wait() {
+                    processes.remove(&name);
+                    drop(processes);
+                    Self::publish_exit(&events, &hooks, &name, pid, code);
+                    let _ = exits.send((name, pid, code));
+                    return;
+                }
             }
-        }
+        });
     }
 
     /// Format a log message with the datetime and that it's from this app.
     ///
     /// NOTE: This is ONLY for logs that originate from Unlimited Ammo, other
     /// log messages from the users app is already formatted.
-    fn format_log_msg(msg: &str) -> String {
+    pub(crate) fn format_log_msg(msg: &str) -> String {
         // Format file update detected message
         let datetime: DateTime<Utc> = Utc::now();
         let datetime = datetime.to_rfc3339_opts(SecondsFormat::Secs, true);
@@ -269,15 +1439,15 @@ impl Watcher {
 
     /// Add a log message to be displayed within the UI.
     pub fn log(&self, msg: &str) {
-        let msg = Self::format_log_msg(msg);
-
-        if let Ok(mut display) = self.display.lock() {
-            display.add_log(msg);
-        }
+        self.events.publish(Event::Log {
+            source: LogSource::Watcher,
+            line: Self::format_log_msg(msg),
+        });
     }
 }
 
 /// Builder Pattern Struct for `Watcher`
+#[derive(Default)]
 pub struct WatcherBuilder {
     /// How fast (in seconds) the file watcher should check for changes.
     watch_interval: Option<u8>,
@@ -285,21 +1455,46 @@ pub struct WatcherBuilder {
     /// The files the file watcher should ignore.
     ignore_list: Option<Vec<String>>,
 
-    /// The list of log messages to display within the UI.
-    display: Option<Arc<Mutex<Display>>>,
+    /// The paths of files the file watcher should ignore.
+    ignored_paths: Vec<PathBuf>,
+
+    /// Where events are published for subscribers.
+    events: Option<EventBus>,
 
-    /// The currently running build process.
-    current_build_process: Option<Arc<Mutex<Option<Child>>>>,
+    /// The running processes.
+    processes: Option<Arc<Mutex<Processes>>>,
+
+    /// Should the stdin of the app be piped ?
+    pipe_stdin: bool,
+
+    /// Should the app run inside a pseudo-terminal ?
+    pty: bool,
+
+    /// When and how to restart the app after it exits on it's own.
+    restart_policy: Option<RestartPolicy>,
+
+    /// How to tell when the app is ready.
+    readiness_probe: Option<ReadinessProbe>,
+
+    /// The shell commands to run around building and running the app.
+    hooks: Option<Hooks>,
+
+    /// What to do about changes to specific paths.
+    triggers: Option<TriggerRules>,
+
+    /// The processes supervised along with the app.
+    procfile: Option<Procfile>,
+
+    /// What to do with the codebase when it changes.
+    mode: Mode,
+
+    /// The arguments passed to clippy after `--`, in clippy mode.
+    clippy_args: Vec<String>,
 }
 impl WatcherBuilder {
     /// Initiate a Builder Pattern Struct for `Watcher`
     pub fn new() -> Self {
-        WatcherBuilder {
-            current_build_process: None,
-            watch_interval: None,
-            ignore_list: None,
-            display: None,
-        }
+        Self::default()
     }
 
     /// Set the watch interval (in seconds) of how fast to poll for changes
@@ -311,26 +1506,119 @@ impl WatcherBuilder {
 
     /// Set the list of files for the `Watcher` to ignore changes
     /// NOTE: This has a default list if not explicitly set
-    pub fn _set_ignore_list(mut self, files_to_ignore: Vec<String>) -> Self {
+    pub fn set_ignore_list(mut self, files_to_ignore: Vec<String>) -> Self {
         self.ignore_list = Some(files_to_ignore);
         self
     }
 
-    /// Set the log display, this is where the log
-    /// messages are displayed within the user interface.
-    pub fn set_display(mut self, display: Arc<Mutex<Display>>) -> Self {
-        self.display = Some(display);
+    /// Ignore changes to a file by it's path (ex: the log file), on top of the ignore list.
+    ///
+    /// NOTE: The file doesn't have to exist yet (ex: a rotated log file),
+    /// so only the directory it's in is resolved (ex: symlinks, `..`).
+    pub fn ignore_path(mut self, path: impl AsRef<Path>) -> Self {
+        let Ok(path) = std::path::absolute(path) else {
+            return self;
+        };
+        let canonical = path
+            .parent()
+            .and_then(|dir| dir.canonicalize().ok())
+            .zip(path.file_name())
+            .map(|(dir, name)| dir.join(name));
+
+        self.ignored_paths.push(canonical.unwrap_or(path));
+        self
+    }
+
+    /// Set the event bus, this is where the events (log messages,
+    /// builds, diagnostics, etc) are published for subscribers.
+    ///
+    /// NOTE: This defaults to a new bus if not explicitly set, which
+    /// can be subscribed to through `Watcher::subscribe`.
+    pub fn set_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
         self
     }
 
-    /// Set the build process for the watcher.
+    /// Set the running processes for the watcher.
     ///
-    /// NOTE: This will always be set as None on
+    /// NOTE: This will always be empty on
     /// initiation, the reason we want to pass it
     /// it in like this rather than defaulting to
-    /// None, is so we can share it amongst threads.
-    pub fn set_build_process(mut self, build_process: Arc<Mutex<Option<Child>>>) -> Self {
-        self.current_build_process = Some(build_process);
+    /// empty, is so we can share it amongst threads
+    /// (ex: killing them when the interface quits).
+    pub fn set_processes(mut self, processes: Arc<Mutex<Processes>>) -> Self {
+        self.processes = Some(processes);
+        self
+    }
+
+    /// Pipe the stdin of the app, so it can be written to through the
+    /// build process (ex: the interface forwarding what the user types).
+    ///
+    /// NOTE: Otherwise the app shares the stdin of the `Watcher`.
+    pub fn set_pipe_stdin(mut self, pipe_stdin: bool) -> Self {
+        self.pipe_stdin = pipe_stdin;
+        self
+    }
+
+    /// Run the app inside a pseudo-terminal, so it sees a real terminal
+    /// (ex: for colors, progress bars) sized with `WatcherCommand::Resize`.
+    ///
+    /// NOTE: Stdout and stderr are then one stream, logged as stdout.
+    pub fn set_pty(mut self, pty: bool) -> Self {
+        self.pty = pty;
+        self
+    }
+
+    /// Set when and how to restart the app after it exits on it's own.
+    ///
+    /// NOTE: Defaults to never restarting it until the next build.
+    pub fn set_restart_policy(mut self, restart_policy: RestartPolicy) -> Self {
+        self.restart_policy = Some(restart_policy);
+        self
+    }
+
+    /// Set how to tell when the app is ready (ex: accepting connections).
+    ///
+    /// NOTE: Without one, the app is ready as soon as it starts.
+    pub fn set_readiness_probe(mut self, readiness_probe: Option<ReadinessProbe>) -> Self {
+        self.readiness_probe = readiness_probe;
+        self
+    }
+
+    /// Set the shell commands to run around building and running the app.
+    pub fn set_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// Set what to do about changes to specific paths.
+    ///
+    /// NOTE: Defaults to rebuilding and restarting the app on
+    /// any change, and building the web frontend along with it.
+    pub fn set_triggers(mut self, triggers: TriggerRules) -> Self {
+        self.triggers = Some(triggers);
+        self
+    }
+
+    /// Set the processes to supervise along with the app (ex: a worker,
+    /// a frontend dev server), restarted on changes to their own paths.
+    pub fn set_procfile(mut self, procfile: Procfile) -> Self {
+        self.procfile = Some(procfile);
+        self
+    }
+
+    /// Set what to do with the codebase when it changes (ex: run the tests).
+    ///
+    /// NOTE: Defaults to building and running the app.
+    pub fn set_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the arguments passed to clippy after `--` in clippy
+    /// mode, usually lint levels (ex: `-W clippy::pedantic`).
+    pub fn set_clippy_args(mut self, clippy_args: Vec<String>) -> Self {
+        self.clippy_args = clippy_args;
         self
     }
 
@@ -345,6 +1633,7 @@ impl WatcherBuilder {
             String::from("node_modules"),
             String::from("tsconfig.tsbuildinfo"),
             String::from("tsconfig.node.tsbuildinfo"),
+            String::from(crate::config::PROJECT_CONFIG_FILE),
         ]));
 
         self
@@ -359,19 +1648,62 @@ impl WatcherBuilder {
         if self.ignore_list.is_none() {
             self = self.set_default_ignore_list();
         }
-        if self.display.is_none() {
-            return Err(Error::DisplayNotSet);
-        }
 
         // NOTE: unwraping here is safe due to the invariant checks above
+        let (command_sender, commands) = mpsc::channel();
+        let (exit_sender, exits) = mpsc::channel();
         let watcher = Watcher {
             watch_interval: self.watch_interval.unwrap(),
             ignore_list: self.ignore_list.unwrap(),
+            ignored_paths: self.ignored_paths,
             targets: HashMap::new(),
-            current_build_process: self.current_build_process.unwrap(),
-            display: self.display.unwrap(),
+            processes: self.processes.unwrap_or_default(),
+            events: self.events.unwrap_or_default(),
+            pipe_stdin: self.pipe_stdin,
+            pty: self.pty,
+            pty_size: (80, 24),
+            commands,
+            command_sender,
+            restart_policy: self.restart_policy.unwrap_or_default(),
+            executable: None,
+            procfile: self.procfile.unwrap_or_default(),
+            supervision: HashMap::new(),
+            readiness_probe: self.readiness_probe,
+            cycle_started_at: Instant::now(),
+            hooks: Arc::new(self.hooks.unwrap_or_default()),
+            triggers: self.triggers.unwrap_or_default(),
+            workspace: None,
+            app_package: None,
+            mode: self.mode,
+            clippy_args: self.clippy_args,
+            build_options: BuildOptions::default(),
+            test_runner: None,
+            failed_tests: Vec::new(),
+            exits,
+            exit_sender,
         };
 
         Ok(watcher)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignored_paths_are_not_watched() {
+        let watcher = WatcherBuilder::new()
+            .set_watch_interval(1)
+            .ignore_path("ammo.log")
+            .ignore_path("./src/../ammo.log.1")
+            .build()
+            .unwrap();
+
+        assert!(!watcher.is_valid_target("ammo.log", Path::new("./ammo.log")));
+        assert!(!watcher.is_valid_target("ammo.log.1", Path::new("./ammo.log.1")));
+        assert!(watcher.is_valid_target("ammo.log", Path::new("./src/ammo.log")));
+        assert!(watcher.is_valid_target("main.rs", Path::new("./src/main.rs")));
+        assert!(!watcher.is_valid_target("target", Path::new("./target")));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{cargo, error::Error};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+/// A package of a cargo workspace, and the members it depends on.
+#[derive(Debug, Clone)]
+struct Member {
+    /// The name of the package (ex: `frontend-utils`).
+    name: String,
+
+    /// The directory of the package (where it's `Cargo.toml` is).
+    dir: PathBuf,
+
+    /// The names of the other members it depends on.
+    dependencies: BTreeSet<String>,
+}
+
+/// The members of a cargo workspace, used to tell which of them are affected by changes.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    members: Vec<Member>,
+}
+impl Workspace {
+    /// Read the members of the workspace the current directory is in with `cargo metadata`.
+    pub fn load() -> Result<Self, Error> {
+        Ok(Self::from_metadata(cargo::Metadata::load()?))
+    }
+
+    /// Create a workspace from the packages described by cargo.
+    fn from_metadata(metadata: cargo::Metadata) -> Self {
+        let names: BTreeSet<String> = metadata
+            .packages
+            .iter()
+            .map(|package| package.name.clone())
+            .collect();
+
+        let members = metadata
+            .packages
+            .into_iter()
+            .map(|package| Member {
+                dir: package
+                    .manifest_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                // NOTE: Dev dependencies are only used by tests,
+                // examples and benchmarks, not the app we run.
+                dependencies: package
+                    .dependencies
+                    .into_iter()
+                    .filter(|dependency| {
+                        dependency.path.is_some()
+                            && dependency.kind.as_deref() != Some("dev")
+                            && names.contains(&dependency.name)
+                    })
+                    .map(|dependency| dependency.name)
+                    .collect(),
+                name: package.name,
+            })
+            .collect();
+
+        Self { members }
+    }
+
+    /// Does the workspace have more than one member ?
+    pub fn has_many_members(&self) -> bool {
+        self.members.len() > 1
+    }
+
+    /// Get the name of the member a path (ex: `./crates/utils/src/lib.rs`) belongs to.
+    ///
+    /// NOTE: Members can be nested, so the deepest one containing the path wins.
+    pub fn member_of(&self, path: &Path) -> Option<&str> {
+        let path = match path.is_absolute() {
+            true => path.to_path_buf(),
+            false => std::env::current_dir()
+                .ok()?
+                .join(path.strip_prefix("./").unwrap_or(path)),
+        };
+
+        self.members
+            .iter()
+            .filter(|member| path.starts_with(&member.dir))
+            .max_by_key(|member| member.dir.components().count())
+            .map(|member| member.name.as_str())
+    }
+
+    /// Get the members affected by changes to a set of paths, the members
+    /// they belong to along with every member depending on those (directly
+    /// or not), or `None` if a path doesn't belong to any member (ex: the
+    /// `Cargo.lock` of a workspace), which could affect every member.
+    pub fn affected_by(&self, paths: &[String]) -> Option<BTreeSet<String>> {
+        let mut affected = paths
+            .iter()
+            .map(|path| self.member_of(Path::new(path)).map(String::from))
+            .collect::<Option<BTreeSet<String>>>()?;
+
+        // NOTE: Keep adding the dependents of the affected members, until there are no new ones.
+        loop {
+            let dependents: Vec<String> = self
+                .members
+                .iter()
+                .filter(|member| {
+                    !affected.contains(&member.name) && !member.dependencies.is_disjoint(&affected)
+                })
+                .map(|member| member.name.clone())
+                .collect();
+            if dependents.is_empty() {
+                return Some(affected);
+            }
+            affected.extend(dependents);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace where `app` depends on `core`, which depends on `utils`.
+    fn chained_workspace() -> Workspace {
+        let member = |name: &str, dependencies: &[&str]| Member {
+            name: name.to_string(),
+            dir: PathBuf::from("/ws/crates").join(name),
+            dependencies: dependencies.iter().map(|name| name.to_string()).collect(),
+        };
+
+        Workspace {
+            members: vec![
+                member("utils", &[]),
+                member("core", &["utils"]),
+                member("app", &["core"]),
+            ],
+        }
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn changes_affect_every_member_depending_on_them() {
+        let workspace = chained_workspace();
+
+        assert_eq!(
+            workspace.affected_by(&[String::from("/ws/crates/utils/src/lib.rs")]),
+            Some(names(&["utils", "core", "app"]))
+        );
+        assert_eq!(
+            workspace.affected_by(&[String::from("/ws/crates/core/src/lib.rs")]),
+            Some(names(&["core", "app"]))
+        );
+        assert_eq!(
+            workspace.affected_by(&[String::from("/ws/crates/app/src/main.rs")]),
+            Some(names(&["app"]))
+        );
+    }
+
+    #[test]
+    fn changes_outside_every_member_could_affect_any() {
+        let workspace = chained_workspace();
+
+        assert_eq!(
+            workspace.affected_by(&[
+                String::from("/ws/crates/app/src/main.rs"),
+                String::from("/ws/Cargo.lock"),
+            ]),
+            None
+        );
+    }
+}